@@ -1,23 +1,57 @@
 use rayon::prelude::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use users::get_current_uid;
 use zbus::Error as ZbusError;
-use zbus::blocking::{Connection, Proxy};
-use zbus::zvariant::{OwnedObjectPath, Value};
+use zbus::Message;
+use zbus::blocking::{Connection, MessageIterator, Proxy};
+use zbus::message::Type as MessageType;
+use zbus::zvariant::{OwnedObjectPath, OwnedValue, Value};
+
+pub mod boot;
+pub mod dependencies;
+pub mod events;
+pub mod failed;
+pub mod jobs;
+pub mod journal;
+pub mod new_service;
+pub mod overrides;
+pub mod processes;
+pub mod templates;
+pub mod timers;
+pub mod unitfile;
+pub mod worker;
+
+use jobs::{JOB_WAIT_TIMEOUT, JobResult, JobWatcher};
 
 const UNIT_ACTION_ID: &str = "org.freedesktop.systemd1.manage-units";
 const UNIT_FILE_ACTION_ID: &str = "org.freedesktop.systemd1.manage-unit-files";
+const RELOAD_ACTION_ID: &str = "org.freedesktop.systemd1.reload-daemon";
+/// pkexec exits with 126 when the user dismissed the dialog and 127 when
+/// polkit denied the request.
+const PKEXEC_NOT_AUTHORIZED: [i32; 2] = [126, 127];
+/// How many `GetAll` calls [`SystemdServiceManager::get_type_infos`] keeps in
+/// flight. Replies queue up in the connection, which drops the oldest once
+/// more than its `max_queued` (64 by default) are waiting.
+const TYPE_INFO_WINDOW: usize = 32;
 
 #[derive(Debug)]
 pub enum ServiceError {
     ZbusError(ZbusError),
     AuthorizationFailed(String),
+    Io(std::io::Error),
+    Journal(String),
+    InvalidUnitName(String),
+    InvalidUnitFile(String),
 }
 impl std::fmt::Display for ServiceError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::ZbusError(e) => write!(f, "D-Bus error: {}", e),
             Self::AuthorizationFailed(e) => write!(f, "Authorization failed: {}", e),
+            Self::Io(e) => write!(f, "I/O error: {}", e),
+            Self::Journal(e) => write!(f, "Journal error: {}", e),
+            Self::InvalidUnitName(e) => write!(f, "Invalid unit name: {}", e),
+            Self::InvalidUnitFile(e) => write!(f, "Invalid unit file: {}", e),
         }
     }
 }
@@ -27,14 +61,177 @@ impl From<ZbusError> for ServiceError {
     }
 }
 
+impl From<std::io::Error> for ServiceError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
 pub type Result<T> = std::result::Result<T, ServiceError>;
 
-#[derive(Debug, Clone)]
-pub struct ServiceInfo {
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitInfo {
     pub name: String,
+    pub unit_type: UnitType,
     pub description: String,
     pub status: ServiceStatus,
     pub enablement_status: EnablementStatus,
+    pub scope: UnitScope,
+    pub sub_state: String,
+    pub load_state: String,
+    /// The queued job as `(id, type)`, if any.
+    pub job: Option<(u32, String)>,
+    /// Extra state of timers, sockets, mounts and paths. `None` for other
+    /// types and for units that are not loaded.
+    pub type_info: Option<UnitTypeInfo>,
+}
+
+impl UnitInfo {
+    /// A unit that has a unit file but is not loaded into the manager. Its
+    /// description is only known once it gets loaded.
+    pub fn not_loaded(
+        name: &str,
+        unit_type: UnitType,
+        enablement_status: EnablementStatus,
+        scope: UnitScope,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            unit_type,
+            description: String::new(),
+            status: ServiceStatus::NotLoaded,
+            enablement_status,
+            scope,
+            sub_state: String::new(),
+            load_state: String::new(),
+            job: None,
+            type_info: None,
+        }
+    }
+}
+
+/// The kinds of units systemd manages, named after their file suffix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnitType {
+    Service,
+    Socket,
+    Target,
+    Timer,
+    Mount,
+    Automount,
+    Path,
+    Slice,
+    Scope,
+    Device,
+    Swap,
+}
+impl UnitType {
+    pub const ALL: [Self; 11] = [
+        Self::Service,
+        Self::Socket,
+        Self::Target,
+        Self::Timer,
+        Self::Mount,
+        Self::Automount,
+        Self::Path,
+        Self::Slice,
+        Self::Scope,
+        Self::Device,
+        Self::Swap,
+    ];
+
+    /// Parses the type from a unit name such as `sshd.socket`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let (_, suffix) = name.rsplit_once('.')?;
+        Self::ALL.into_iter().find(|t| t.suffix() == suffix)
+    }
+
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            Self::Service => "service",
+            Self::Socket => "socket",
+            Self::Target => "target",
+            Self::Timer => "timer",
+            Self::Mount => "mount",
+            Self::Automount => "automount",
+            Self::Path => "path",
+            Self::Slice => "slice",
+            Self::Scope => "scope",
+            Self::Device => "device",
+            Self::Swap => "swap",
+        }
+    }
+
+    /// The D-Bus interface whose properties end up in [`UnitTypeInfo`], for
+    /// the types that have any.
+    pub(crate) fn info_interface(&self) -> Option<&'static str> {
+        match self {
+            Self::Timer => Some("org.freedesktop.systemd1.Timer"),
+            Self::Socket => Some("org.freedesktop.systemd1.Socket"),
+            Self::Mount => Some("org.freedesktop.systemd1.Mount"),
+            Self::Path => Some("org.freedesktop.systemd1.Path"),
+            _ => None,
+        }
+    }
+}
+
+/// Type-specific state shown in the unit list. Timestamps are microseconds
+/// with 0 meaning "never".
+#[derive(Debug, Clone, PartialEq)]
+pub enum UnitTypeInfo {
+    Timer {
+        /// Wall clock time of the next elapse, for calendar timers.
+        next_elapse_realtime: u64,
+        /// `CLOCK_MONOTONIC` time of the next elapse, for `OnBootSec=` and
+        /// friends.
+        next_elapse_monotonic: u64,
+        last_trigger: u64,
+        /// The unit the timer activates.
+        unit: String,
+    },
+    Socket {
+        /// `Listen*=` entries as `(type, address)`.
+        listen: Vec<(String, String)>,
+    },
+    Mount {
+        what: String,
+    },
+    Path {
+        /// Watched paths as `(condition, path)`.
+        paths: Vec<(String, String)>,
+    },
+}
+impl UnitTypeInfo {
+    fn from_properties(unit_type: UnitType, properties: &Properties) -> Option<Self> {
+        let string = |key| property::<String>(properties, key).unwrap_or_default();
+        let pairs = |key| property::<Vec<(String, String)>>(properties, key).unwrap_or_default();
+        match unit_type {
+            UnitType::Timer => Some(Self::Timer {
+                next_elapse_realtime: property(properties, "NextElapseUSecRealtime").unwrap_or(0),
+                next_elapse_monotonic: property(properties, "NextElapseUSecMonotonic").unwrap_or(0),
+                last_trigger: property(properties, "LastTriggerUSec").unwrap_or(0),
+                unit: string("Unit"),
+            }),
+            UnitType::Socket => Some(Self::Socket {
+                listen: pairs("Listen"),
+            }),
+            UnitType::Mount => Some(Self::Mount {
+                what: string("What"),
+            }),
+            UnitType::Path => Some(Self::Path {
+                paths: pairs("Paths"),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Which systemd manager a unit belongs to: the system instance or the
+/// per-user instance reachable over the session bus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnitScope {
+    System,
+    User,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -44,6 +241,8 @@ pub enum ServiceStatus {
     Failed,
     Activating,
     Deactivating,
+    /// Installed, but not loaded by the manager. Never parsed from a string.
+    NotLoaded,
     Unknown(String),
 }
 impl From<&str> for ServiceStatus {
@@ -67,6 +266,8 @@ pub enum EnablementStatus {
     Indirect,
     Generated,
     Transient,
+    Masked,
+    MaskedRuntime,
     Unknown(String),
 }
 impl From<&str> for EnablementStatus {
@@ -78,16 +279,344 @@ impl From<&str> for EnablementStatus {
             "indirect" => Self::Indirect,
             "generated" => Self::Generated,
             "transient" => Self::Transient,
+            "masked" => Self::Masked,
+            "masked-runtime" => Self::MaskedRuntime,
             _ => Self::Unknown(s.to_string()),
         }
     }
 }
 
+/// Options shared by the unit file enable/disable calls.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UnitFileOptions {
+    /// Write links below `/run` so the change is gone after a reboot.
+    pub runtime: bool,
+    /// Replace existing links that point elsewhere.
+    pub force: bool,
+}
+impl UnitFileOptions {
+    const SD_SYSTEMD_UNIT_RUNTIME: u64 = 1 << 0;
+    const SD_SYSTEMD_UNIT_FORCE: u64 = 1 << 1;
+
+    fn flags(&self) -> u64 {
+        let mut flags = 0;
+        if self.runtime {
+            flags |= Self::SD_SYSTEMD_UNIT_RUNTIME;
+        }
+        if self.force {
+            flags |= Self::SD_SYSTEMD_UNIT_FORCE;
+        }
+        flags
+    }
+}
+
+/// Which of a unit's processes `KillUnit` signals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KillWhom {
+    /// The main process of a service.
+    Main,
+    /// The process running an `ExecStartPre=`, `ExecReload=` or similar
+    /// command, if any.
+    Control,
+    All,
+}
+
+impl KillWhom {
+    pub const ALL: [KillWhom; 3] = [Self::Main, Self::Control, Self::All];
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Main => "main",
+            Self::Control => "control",
+            Self::All => "all",
+        }
+    }
+}
+
+/// The signals offered for sending to units and processes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    Term,
+    Kill,
+    Hup,
+    Int,
+    Quit,
+    Usr1,
+    Usr2,
+    Stop,
+    Cont,
+}
+
+impl Signal {
+    pub const ALL: [Signal; 9] = [
+        Self::Term,
+        Self::Kill,
+        Self::Hup,
+        Self::Int,
+        Self::Quit,
+        Self::Usr1,
+        Self::Usr2,
+        Self::Stop,
+        Self::Cont,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        signal_name(self.number()).unwrap_or("unknown signal")
+    }
+
+    pub fn number(&self) -> i32 {
+        match self {
+            Self::Term => libc::SIGTERM,
+            Self::Kill => libc::SIGKILL,
+            Self::Hup => libc::SIGHUP,
+            Self::Int => libc::SIGINT,
+            Self::Quit => libc::SIGQUIT,
+            Self::Usr1 => libc::SIGUSR1,
+            Self::Usr2 => libc::SIGUSR2,
+            Self::Stop => libc::SIGSTOP,
+            Self::Cont => libc::SIGCONT,
+        }
+    }
+}
+
+/// Names of the standard Linux signals. Real-time signals have none.
+const SIGNAL_NAMES: [(i32, &str); 31] = [
+    (libc::SIGHUP, "SIGHUP"),
+    (libc::SIGINT, "SIGINT"),
+    (libc::SIGQUIT, "SIGQUIT"),
+    (libc::SIGILL, "SIGILL"),
+    (libc::SIGTRAP, "SIGTRAP"),
+    (libc::SIGABRT, "SIGABRT"),
+    (libc::SIGBUS, "SIGBUS"),
+    (libc::SIGFPE, "SIGFPE"),
+    (libc::SIGKILL, "SIGKILL"),
+    (libc::SIGUSR1, "SIGUSR1"),
+    (libc::SIGSEGV, "SIGSEGV"),
+    (libc::SIGUSR2, "SIGUSR2"),
+    (libc::SIGPIPE, "SIGPIPE"),
+    (libc::SIGALRM, "SIGALRM"),
+    (libc::SIGTERM, "SIGTERM"),
+    (libc::SIGSTKFLT, "SIGSTKFLT"),
+    (libc::SIGCHLD, "SIGCHLD"),
+    (libc::SIGCONT, "SIGCONT"),
+    (libc::SIGSTOP, "SIGSTOP"),
+    (libc::SIGTSTP, "SIGTSTP"),
+    (libc::SIGTTIN, "SIGTTIN"),
+    (libc::SIGTTOU, "SIGTTOU"),
+    (libc::SIGURG, "SIGURG"),
+    (libc::SIGXCPU, "SIGXCPU"),
+    (libc::SIGXFSZ, "SIGXFSZ"),
+    (libc::SIGVTALRM, "SIGVTALRM"),
+    (libc::SIGPROF, "SIGPROF"),
+    (libc::SIGWINCH, "SIGWINCH"),
+    (libc::SIGIO, "SIGIO"),
+    (libc::SIGPWR, "SIGPWR"),
+    (libc::SIGSYS, "SIGSYS"),
+];
+
+/// The name of a signal number, e.g. `SIGSEGV` for 11.
+pub fn signal_name(number: i32) -> Option<&'static str> {
+    SIGNAL_NAMES
+        .iter()
+        .find(|(signal, _)| *signal == number)
+        .map(|(_, name)| *name)
+}
+
+/// One entry of the change list returned by the unit file methods, e.g. a
+/// symlink that was created or removed.
+#[derive(Debug, Clone)]
+pub struct UnitFileChange {
+    pub change_type: String,
+    pub file: String,
+    pub destination: String,
+}
+impl From<(String, String, String)> for UnitFileChange {
+    fn from((change_type, file, destination): (String, String, String)) -> Self {
+        Self {
+            change_type,
+            file,
+            destination,
+        }
+    }
+}
+impl std::fmt::Display for UnitFileChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.change_type.as_str() {
+            "symlink" => write!(f, "Created symlink {} → {}", self.file, self.destination),
+            "unlink" => write!(f, "Removed {}", self.file),
+            other => write!(f, "{} {} {}", other, self.file, self.destination),
+        }
+    }
+}
+
+/// Reply of a unit file operation.
 #[derive(Debug, Clone)]
-struct UnitInfo {
+pub struct UnitFileChanges {
+    /// Whether the unit has an `[Install]` section. Only `EnableUnitFiles`
+    /// reports this, so it is `None` for every other operation.
+    pub carries_install_info: Option<bool>,
+    pub changes: Vec<UnitFileChange>,
+}
+impl UnitFileChanges {
+    fn new(carries_install_info: Option<bool>, changes: Vec<(String, String, String)>) -> Self {
+        Self {
+            carries_install_info,
+            changes: changes.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// True when systemd accepted the request but had nothing to install,
+    /// which is what happens for units without an `[Install]` section.
+    pub fn lacks_install_info(&self) -> bool {
+        self.carries_install_info == Some(false)
+    }
+}
+
+/// Properties of a single unit as shown in the detail view. Timestamps are
+/// microseconds since the epoch with 0 meaning "never".
+#[derive(Debug, Clone)]
+pub struct UnitDetails {
+    pub id: String,
+    pub description: String,
+    pub load_state: String,
+    pub active_state: String,
+    pub sub_state: String,
+    pub unit_file_state: String,
+    pub fragment_path: String,
+    pub active_enter_timestamp: u64,
+    pub active_exit_timestamp: u64,
+    pub inactive_exit_timestamp: u64,
+    pub state_change_timestamp: u64,
+    pub service: Option<ServiceDetails>,
+}
+
+/// Properties of the `org.freedesktop.systemd1.Service` interface. Resource
+/// counters are `None` when accounting is off, which systemd reports as
+/// `u64::MAX`.
+#[derive(Debug, Clone)]
+pub struct ServiceDetails {
+    pub service_type: String,
+    pub restart: String,
+    pub result: String,
+    pub main_pid: u32,
+    pub exec_start: Vec<String>,
+    pub n_restarts: u32,
+    pub memory_current: Option<u64>,
+    pub cpu_usage_nsec: Option<u64>,
+    pub tasks_current: Option<u64>,
+}
+
+/// `ExecStart=` entries as returned over D-Bus: path, argv, ignore-failure
+/// flag, start/exit timestamps, pid and exit code/status.
+type ExecCommand = (String, Vec<String>, bool, u64, u64, u64, u64, u32, i32, i32);
+
+type Properties = HashMap<String, OwnedValue>;
+
+fn property<T: TryFrom<OwnedValue>>(properties: &Properties, key: &str) -> Option<T> {
+    properties
+        .get(key)
+        .and_then(|v| v.try_clone().ok())
+        .and_then(|v| T::try_from(v).ok())
+}
+
+fn counter_property(properties: &Properties, key: &str) -> Option<u64> {
+    property::<u64>(properties, key).filter(|v| *v != u64::MAX)
+}
+
+impl UnitDetails {
+    fn from_properties(unit: &Properties, service: Option<&Properties>) -> Self {
+        let string = |key| property::<String>(unit, key).unwrap_or_default();
+        let timestamp = |key| property::<u64>(unit, key).unwrap_or(0);
+        Self {
+            id: string("Id"),
+            description: string("Description"),
+            load_state: string("LoadState"),
+            active_state: string("ActiveState"),
+            sub_state: string("SubState"),
+            unit_file_state: string("UnitFileState"),
+            fragment_path: string("FragmentPath"),
+            active_enter_timestamp: timestamp("ActiveEnterTimestamp"),
+            active_exit_timestamp: timestamp("ActiveExitTimestamp"),
+            inactive_exit_timestamp: timestamp("InactiveExitTimestamp"),
+            state_change_timestamp: timestamp("StateChangeTimestamp"),
+            service: service.map(ServiceDetails::from_properties),
+        }
+    }
+}
+
+impl ServiceDetails {
+    fn from_properties(service: &Properties) -> Self {
+        let string = |key| property::<String>(service, key).unwrap_or_default();
+        Self {
+            service_type: string("Type"),
+            restart: string("Restart"),
+            result: string("Result"),
+            main_pid: property(service, "MainPID").unwrap_or(0),
+            exec_start: property::<Vec<ExecCommand>>(service, "ExecStart")
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(_, argv, ..)| argv.join(" "))
+                .collect(),
+            n_restarts: property(service, "NRestarts").unwrap_or(0),
+            memory_current: counter_property(service, "MemoryCurrent"),
+            cpu_usage_nsec: counter_property(service, "CPUUsageNSec"),
+            tasks_current: counter_property(service, "TasksCurrent"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ListedUnit {
     pub name: String,
+    pub path: OwnedObjectPath,
     pub description: String,
+    pub load_state: String,
     pub active_state: String,
+    pub sub_state: String,
+    pub job: Option<(u32, String)>,
+}
+
+/// Decodes a `ListUnits` or `ListUnitsByPatterns` reply.
+fn listed_units(msg: &zbus::Message) -> Result<Vec<ListedUnit>> {
+    let units = msg
+        .body()
+        .deserialize::<Vec<(
+            String,
+            String,
+            String,
+            String,
+            String,
+            String,
+            OwnedObjectPath,
+            u32,
+            String,
+            OwnedObjectPath,
+        )>>()?
+        .into_iter()
+        .map(
+            |(
+                name,
+                description,
+                load_state,
+                active_state,
+                sub_state,
+                _,
+                path,
+                job_id,
+                job_type,
+                _,
+            )| ListedUnit {
+                name,
+                path,
+                description,
+                load_state,
+                active_state,
+                sub_state,
+                job: (job_id != 0).then_some((job_id, job_type)),
+            },
+        )
+        .collect();
+    Ok(units)
 }
 
 #[derive(Clone)]
@@ -98,10 +627,10 @@ impl SystemdServiceManager {
         Self
     }
 
-    pub fn get_services(&self) -> Result<Vec<ServiceInfo>> {
+    pub fn get_units(&self) -> Result<Vec<UnitInfo>> {
         let (system_result, session_result) = rayon::join(
-            || self.fetch_services(Connection::system()),
-            || self.fetch_services(Connection::session()),
+            || self.fetch_units(Connection::system(), UnitScope::System),
+            || self.fetch_units(Connection::session(), UnitScope::User),
         );
         let mut services = Vec::new();
         if let Ok(s) = system_result {
@@ -113,7 +642,11 @@ impl SystemdServiceManager {
         Ok(services)
     }
 
-    fn fetch_services(&self, conn_result: zbus::Result<Connection>) -> Result<Vec<ServiceInfo>> {
+    fn fetch_units(
+        &self,
+        conn_result: zbus::Result<Connection>,
+        scope: UnitScope,
+    ) -> Result<Vec<UnitInfo>> {
         let conn = conn_result?;
         let (units, unit_files) = rayon::join(
             || self.call_list_units(&conn),
@@ -130,22 +663,130 @@ impl SystemdServiceManager {
             })
             .collect();
 
-        Ok(units
+        let typed_units: Vec<(OwnedObjectPath, UnitType)> = units
+            .iter()
+            .filter_map(|unit| Some((unit.path.clone(), UnitType::from_name(&unit.name)?)))
+            .collect();
+        let type_infos = self.get_type_infos(&conn, &typed_units);
+
+        let loaded: HashSet<&str> = units.iter().map(|u| u.name.as_str()).collect();
+        let not_loaded: Vec<UnitInfo> = enablement_map
+            .iter()
+            .filter(|(name, _)| !loaded.contains(name.as_str()))
+            .filter_map(|(name, state)| {
+                let unit_type = UnitType::from_name(name)?;
+                Some(UnitInfo::not_loaded(
+                    name,
+                    unit_type,
+                    state.as_str().into(),
+                    scope,
+                ))
+            })
+            .collect();
+
+        let mut units: Vec<UnitInfo> = units
             .into_par_iter()
-            .filter(|u| u.name.ends_with(".service"))
-            .map(|unit| ServiceInfo {
+            .filter_map(|unit| {
+                let unit_type = UnitType::from_name(&unit.name)?;
+                Some((unit, unit_type))
+            })
+            .map(|(unit, unit_type)| UnitInfo {
+                type_info: type_infos.get(&unit.path).cloned(),
                 name: unit.name.to_owned(),
+                unit_type,
                 description: unit.description,
                 status: unit.active_state.as_str().into(),
                 enablement_status: enablement_map
                     .get(&unit.name)
                     .map(|s| s.as_str().into())
                     .unwrap_or(EnablementStatus::Unknown("unknown".to_string())),
+                scope,
+                sub_state: unit.sub_state,
+                load_state: unit.load_state,
+                job: unit.job,
             })
-            .collect())
+            .collect();
+        units.extend(not_loaded);
+        Ok(units)
     }
 
-    fn call_list_units(&self, conn: &Connection) -> Result<Vec<UnitInfo>> {
+    /// Reads the [`UnitTypeInfo`] of a loaded unit. Errors are swallowed, as
+    /// the list is still useful without the extra column.
+    fn get_type_info(
+        &self,
+        conn: &Connection,
+        path: &OwnedObjectPath,
+        unit_type: UnitType,
+    ) -> Option<UnitTypeInfo> {
+        let interface = unit_type.info_interface()?;
+        let properties = self.get_all_properties(conn, path, interface).ok()?;
+        UnitTypeInfo::from_properties(unit_type, &properties)
+    }
+
+    /// Reads the [`UnitTypeInfo`] of many loaded units. The `GetAll` calls
+    /// are pipelined on the connection instead of waiting for each reply in
+    /// turn, so a list fetch does not cost a round trip per timer, socket,
+    /// mount and path unit. Failed calls are left out, like in
+    /// [`Self::get_type_info`].
+    fn get_type_infos(
+        &self,
+        conn: &Connection,
+        units: &[(OwnedObjectPath, UnitType)],
+    ) -> HashMap<OwnedObjectPath, UnitTypeInfo> {
+        let mut type_infos = HashMap::new();
+        // Created before the first call goes out, so no reply is missed.
+        let mut replies = MessageIterator::from(conn);
+        let mut requests = units
+            .iter()
+            .filter_map(|(path, unit_type)| Some((path, *unit_type, unit_type.info_interface()?)));
+        let mut pending = HashMap::new();
+        loop {
+            while pending.len() < TYPE_INFO_WINDOW {
+                let Some((path, unit_type, interface)) = requests.next() else {
+                    break;
+                };
+                let sent = Message::method_call(path.as_ref(), "GetAll")
+                    .and_then(|call| call.destination("org.freedesktop.systemd1"))
+                    .and_then(|call| call.interface("org.freedesktop.DBus.Properties"))
+                    .and_then(|call| call.build(&(interface,)))
+                    .and_then(|call| {
+                        conn.send(&call)?;
+                        Ok(call.primary_header().serial_num())
+                    });
+                if let Ok(serial) = sent {
+                    pending.insert(serial, (path, unit_type));
+                }
+            }
+            if pending.is_empty() {
+                break;
+            }
+            // The iterator only fails or ends once the connection is gone.
+            let Some(Ok(reply)) = replies.next() else {
+                break;
+            };
+            let Some((path, unit_type)) = reply
+                .header()
+                .reply_serial()
+                .and_then(|serial| pending.remove(&serial))
+            else {
+                continue;
+            };
+            if reply.message_type() != MessageType::MethodReturn {
+                continue;
+            }
+            let type_info = reply
+                .body()
+                .deserialize::<Properties>()
+                .ok()
+                .and_then(|properties| UnitTypeInfo::from_properties(unit_type, &properties));
+            if let Some(type_info) = type_info {
+                type_infos.insert(path.clone(), type_info);
+            }
+        }
+        type_infos
+    }
+
+    fn call_list_units(&self, conn: &Connection) -> Result<Vec<ListedUnit>> {
         let msg = conn.call_method(
             Some("org.freedesktop.systemd1"),
             "/org/freedesktop/systemd1",
@@ -153,28 +794,22 @@ impl SystemdServiceManager {
             "ListUnits",
             &(),
         )?;
-        let units: Vec<_> = msg
-            .body()
-            .deserialize::<Vec<(
-                String,
-                String,
-                String,
-                String,
-                String,
-                String,
-                OwnedObjectPath,
-                u32,
-                String,
-                OwnedObjectPath,
-            )>>()?
+        listed_units(&msg)
+    }
+
+    /// Looks a unit up among the units the manager has in memory. Unlike
+    /// `LoadUnit` this never loads it, so no `UnitNew` is emitted.
+    fn find_listed_unit(&self, conn: &Connection, unit_name: &str) -> Result<Option<ListedUnit>> {
+        let msg = conn.call_method(
+            Some("org.freedesktop.systemd1"),
+            "/org/freedesktop/systemd1",
+            Some("org.freedesktop.systemd1.Manager"),
+            "ListUnitsByPatterns",
+            &(Vec::<&str>::new(), vec![unit_name]),
+        )?;
+        Ok(listed_units(&msg)?
             .into_iter()
-            .map(|(name, description, _, active_state, ..)| UnitInfo {
-                name,
-                description,
-                active_state,
-            })
-            .collect();
-        Ok(units)
+            .find(|unit| unit.name == unit_name))
     }
 
     fn call_list_unit_files(&self, conn: &Connection) -> Result<Vec<(String, String)>> {
@@ -201,34 +836,296 @@ impl SystemdServiceManager {
         Ok(conn)
     }
 
-    pub fn start_unit(&self, unit_name: &str) -> Result<()> {
-        let conn = self.get_authorized_connection(UNIT_ACTION_ID)?;
+    /// User units are managed by the caller's own systemd instance, so they
+    /// go over the session bus and need no polkit check.
+    fn get_scoped_connection(&self, scope: UnitScope, action_id: &str) -> Result<Connection> {
+        match scope {
+            UnitScope::System => self.get_authorized_connection(action_id),
+            UnitScope::User => self.get_connection(scope),
+        }
+    }
+
+    /// Queues a job through one of the Manager's `*Unit(name, mode)` methods
+    /// and blocks until systemd reports its result.
+    fn queue_unit_job(&self, method: &str, unit_name: &str, scope: UnitScope) -> Result<JobResult> {
+        let watcher = JobWatcher::new(self, scope)?;
+        let conn = self.get_scoped_connection(scope, UNIT_ACTION_ID)?;
+        let proxy = self.get_manager_proxy(&conn)?;
+        let job_path: OwnedObjectPath = proxy.call(method, &(unit_name, "replace"))?;
+        Ok(watcher.wait(&job_path, JOB_WAIT_TIMEOUT))
+    }
+
+    pub fn start_unit(&self, unit_name: &str, scope: UnitScope) -> Result<JobResult> {
+        self.queue_unit_job("StartUnit", unit_name, scope)
+    }
+
+    pub fn stop_unit(&self, unit_name: &str, scope: UnitScope) -> Result<JobResult> {
+        self.queue_unit_job("StopUnit", unit_name, scope)
+    }
+
+    pub fn restart_unit(&self, unit_name: &str, scope: UnitScope) -> Result<JobResult> {
+        self.queue_unit_job("RestartUnit", unit_name, scope)
+    }
+
+    pub fn reload_unit(&self, unit_name: &str, scope: UnitScope) -> Result<JobResult> {
+        self.queue_unit_job("ReloadUnit", unit_name, scope)
+    }
+
+    pub fn try_restart_unit(&self, unit_name: &str, scope: UnitScope) -> Result<JobResult> {
+        self.queue_unit_job("TryRestartUnit", unit_name, scope)
+    }
+
+    pub fn reload_or_restart_unit(&self, unit_name: &str, scope: UnitScope) -> Result<JobResult> {
+        self.queue_unit_job("ReloadOrRestartUnit", unit_name, scope)
+    }
+
+    /// Sends `signal` to the unit's processes selected by `whom`. Unlike a
+    /// stop job this does not change the unit's state by itself.
+    pub fn kill_unit(
+        &self,
+        unit_name: &str,
+        whom: KillWhom,
+        signal: Signal,
+        scope: UnitScope,
+    ) -> Result<()> {
+        let conn = self.get_scoped_connection(scope, UNIT_ACTION_ID)?;
         let proxy = self.get_manager_proxy(&conn)?;
-        proxy.call_method("StartUnit", &(unit_name, "replace"))?;
+        proxy.call_method("KillUnit", &(unit_name, whom.as_str(), signal.number()))?;
         Ok(())
     }
 
-    pub fn stop_unit(&self, unit_name: &str) -> Result<()> {
-        let conn = self.get_authorized_connection(UNIT_ACTION_ID)?;
+    /// Clears the failed state and restart counter of one unit, like
+    /// `systemctl reset-failed <unit>`.
+    pub fn reset_failed_unit(&self, unit_name: &str, scope: UnitScope) -> Result<()> {
+        let conn = self.get_scoped_connection(scope, UNIT_ACTION_ID)?;
         let proxy = self.get_manager_proxy(&conn)?;
-        proxy.call_method("StopUnit", &(unit_name, "replace"))?;
+        proxy.call_method("ResetFailedUnit", &(unit_name,))?;
         Ok(())
     }
 
-    pub fn enable_unit(&self, unit_name: &str) -> Result<()> {
-        let conn = self.get_authorized_connection(UNIT_FILE_ACTION_ID)?;
+    /// Clears the failed state of every unit of the manager.
+    pub fn reset_failed(&self, scope: UnitScope) -> Result<()> {
+        let conn = self.get_scoped_connection(scope, UNIT_ACTION_ID)?;
         let proxy = self.get_manager_proxy(&conn)?;
-        proxy.call_method("EnableUnitFiles", &(vec![unit_name], false, true))?;
+        proxy.call_method("ResetFailed", &())?;
         Ok(())
     }
 
-    pub fn disable_unit(&self, unit_name: &str) -> Result<()> {
-        let conn = self.get_authorized_connection(UNIT_FILE_ACTION_ID)?;
+    /// Reads the unit's `CanReload` property. A unit that is not in memory
+    /// is not loaded for this and reports `false`.
+    pub fn can_reload(&self, unit_name: &str, scope: UnitScope) -> Result<bool> {
+        let conn = self.get_connection(scope)?;
+        let Some(unit) = self.find_listed_unit(&conn, unit_name)? else {
+            return Ok(false);
+        };
+        let unit_proxy = self.get_unit_proxy(&conn, unit.path, "org.freedesktop.systemd1.Unit")?;
+        unit_proxy.get_property("CanReload").map_err(Into::into)
+    }
+
+    /// Fetches the `Unit` properties of a unit, plus the `Service` ones for
+    /// `.service` units. Units that are only installed are described from
+    /// their unit file instead: loading them would emit `UnitNew`, which
+    /// refreshes the list and with it the details again.
+    pub fn get_unit_details(&self, unit_name: &str, scope: UnitScope) -> Result<UnitDetails> {
+        let conn = self.get_connection(scope)?;
+        let Some(listed) = self.find_listed_unit(&conn, unit_name)? else {
+            return self.unit_file_details(&conn, unit_name);
+        };
+        let unit_path = listed.path;
+        let unit = self.get_all_properties(&conn, &unit_path, "org.freedesktop.systemd1.Unit")?;
+        let service = if unit_name.ends_with(".service") {
+            Some(self.get_all_properties(&conn, &unit_path, "org.freedesktop.systemd1.Service")?)
+        } else {
+            None
+        };
+        Ok(UnitDetails::from_properties(&unit, service.as_ref()))
+    }
+
+    /// Details of a unit that is not in memory, from `ListUnitFilesByPatterns`
+    /// and the `Description=` of the file itself.
+    fn unit_file_details(&self, conn: &Connection, unit_name: &str) -> Result<UnitDetails> {
+        let unit_files: Vec<(String, String)> = conn
+            .call_method(
+                Some("org.freedesktop.systemd1"),
+                "/org/freedesktop/systemd1",
+                Some("org.freedesktop.systemd1.Manager"),
+                "ListUnitFilesByPatterns",
+                &(Vec::<&str>::new(), vec![unit_name]),
+            )?
+            .body()
+            .deserialize()?;
+        let (path, state) = unit_files
+            .into_iter()
+            .find(|(path, _)| {
+                std::path::Path::new(path)
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    == Some(unit_name)
+            })
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("Unit {} not found", unit_name),
+                )
+            })?;
+        let description = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|source| {
+                unitfile::UnitFile::parse(&source)
+                    .value("Unit", "Description")
+                    .map(str::to_string)
+            })
+            .unwrap_or_default();
+        Ok(UnitDetails {
+            id: unit_name.to_string(),
+            description,
+            load_state: "not-loaded".to_string(),
+            active_state: "inactive".to_string(),
+            sub_state: "dead".to_string(),
+            unit_file_state: state,
+            fragment_path: path,
+            active_enter_timestamp: 0,
+            active_exit_timestamp: 0,
+            inactive_exit_timestamp: 0,
+            state_change_timestamp: 0,
+            service: None,
+        })
+    }
+
+    fn get_connection(&self, scope: UnitScope) -> Result<Connection> {
+        match scope {
+            UnitScope::System => Connection::system().map_err(Into::into),
+            UnitScope::User => Connection::session().map_err(Into::into),
+        }
+    }
+
+    fn get_unit_path(&self, conn: &Connection, unit_name: &str) -> Result<OwnedObjectPath> {
+        self.get_manager_proxy(conn)?
+            .call("GetUnit", &(unit_name,))
+            .map_err(Into::into)
+    }
+
+    /// Like [`Self::get_unit_path`], but loads the unit from its file if it
+    /// is not in memory. systemd unloads it again once it is unused.
+    fn load_unit_path(&self, conn: &Connection, unit_name: &str) -> Result<OwnedObjectPath> {
+        self.get_manager_proxy(conn)?
+            .call("LoadUnit", &(unit_name,))
+            .map_err(Into::into)
+    }
+
+    fn get_unit_proxy<'a>(
+        &self,
+        conn: &'a Connection,
+        unit_path: OwnedObjectPath,
+        interface: &'static str,
+    ) -> Result<Proxy<'a>> {
+        Proxy::new(conn, "org.freedesktop.systemd1", unit_path, interface).map_err(Into::into)
+    }
+
+    fn get_all_properties(
+        &self,
+        conn: &Connection,
+        unit_path: &OwnedObjectPath,
+        interface: &str,
+    ) -> Result<Properties> {
+        conn.call_method(
+            Some("org.freedesktop.systemd1"),
+            unit_path,
+            Some("org.freedesktop.DBus.Properties"),
+            "GetAll",
+            &(interface,),
+        )?
+        .body()
+        .deserialize()
+        .map_err(Into::into)
+    }
+
+    pub fn enable_unit(
+        &self,
+        unit_name: &str,
+        options: UnitFileOptions,
+        scope: UnitScope,
+    ) -> Result<UnitFileChanges> {
+        let conn = self.get_scoped_connection(scope, UNIT_FILE_ACTION_ID)?;
         let proxy = self.get_manager_proxy(&conn)?;
-        proxy.call_method("DisableUnitFiles", &(vec![unit_name], false))?;
+        let (carries_install_info, changes): (bool, Vec<(String, String, String)>) = proxy.call(
+            "EnableUnitFiles",
+            &(vec![unit_name], options.runtime, options.force),
+        )?;
+        Ok(UnitFileChanges::new(Some(carries_install_info), changes))
+    }
+
+    /// Uses `DisableUnitFilesWithFlags` since plain `DisableUnitFiles` has no
+    /// force argument.
+    pub fn disable_unit(
+        &self,
+        unit_name: &str,
+        options: UnitFileOptions,
+        scope: UnitScope,
+    ) -> Result<UnitFileChanges> {
+        let conn = self.get_scoped_connection(scope, UNIT_FILE_ACTION_ID)?;
+        let proxy = self.get_manager_proxy(&conn)?;
+        let changes: Vec<(String, String, String)> = proxy.call(
+            "DisableUnitFilesWithFlags",
+            &(vec![unit_name], options.flags()),
+        )?;
+        Ok(UnitFileChanges::new(None, changes))
+    }
+
+    /// Masks the unit by linking it to `/dev/null`. A runtime mask lives under
+    /// `/run` and disappears on reboot.
+    pub fn mask_unit(
+        &self,
+        unit_name: &str,
+        runtime: bool,
+        scope: UnitScope,
+    ) -> Result<UnitFileChanges> {
+        let conn = self.get_scoped_connection(scope, UNIT_FILE_ACTION_ID)?;
+        let proxy = self.get_manager_proxy(&conn)?;
+        let changes: Vec<(String, String, String)> =
+            proxy.call("MaskUnitFiles", &(vec![unit_name], runtime, false))?;
+        Ok(UnitFileChanges::new(None, changes))
+    }
+
+    /// Removes a mask. `runtime` has to match how the unit was masked.
+    pub fn unmask_unit(
+        &self,
+        unit_name: &str,
+        runtime: bool,
+        scope: UnitScope,
+    ) -> Result<UnitFileChanges> {
+        let conn = self.get_scoped_connection(scope, UNIT_FILE_ACTION_ID)?;
+        let proxy = self.get_manager_proxy(&conn)?;
+        let changes: Vec<(String, String, String)> =
+            proxy.call("UnmaskUnitFiles", &(vec![unit_name], runtime))?;
+        Ok(UnitFileChanges::new(None, changes))
+    }
+
+    /// Makes the manager re-read all unit files, like `systemctl daemon-reload`.
+    pub fn daemon_reload(&self, scope: UnitScope) -> Result<()> {
+        let conn = self.get_scoped_connection(scope, RELOAD_ACTION_ID)?;
+        let proxy = self.get_manager_proxy(&conn)?;
+        proxy.call_method("Reload", &())?;
         Ok(())
     }
 
+    /// Re-executes the manager binary, like `systemctl daemon-reexec`. The
+    /// manager drops off the bus while restarting, so the reply may be lost.
+    pub fn daemon_reexec(&self, scope: UnitScope) -> Result<()> {
+        let conn = self.get_scoped_connection(scope, RELOAD_ACTION_ID)?;
+        let proxy = self.get_manager_proxy(&conn)?;
+        match proxy.call_method("Reexecute", &()) {
+            Err(ZbusError::MethodError(name, ..))
+                if name == "org.freedesktop.DBus.Error.NoReply"
+                    || name == "org.freedesktop.DBus.Error.Disconnected" =>
+            {
+                Ok(())
+            }
+            Err(e) => Err(e.into()),
+            Ok(_) => Ok(()),
+        }
+    }
+
     fn get_manager_proxy<'a>(&self, conn: &'a Connection) -> Result<Proxy<'a>> {
         Proxy::new(
             conn,
@@ -239,7 +1136,7 @@ impl SystemdServiceManager {
         .map_err(Into::into)
     }
 
-    fn authorize<'a>(&self, action_id: &str, conn: &'a Connection) -> Result<bool> {
+    fn authorize(&self, action_id: &str, conn: &Connection) -> Result<bool> {
         let subject = (
             "unix-process",
             HashMap::from([
@@ -0,0 +1,165 @@
+//! Boot performance, as `systemd-analyze time`, `blame` and
+//! `critical-chain` compute it.
+//!
+//! All timestamps are `CLOCK_MONOTONIC` microseconds. Firmware and boot
+//! loader run before the kernel starts that clock, so systemd reports how
+//! long before zero they started.
+
+use super::{Properties, Result, SystemdServiceManager, UnitScope, property};
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+const UNIT_INTERFACE: &str = "org.freedesktop.systemd1.Unit";
+
+/// How long each boot phase took. A phase the manager has no timestamp for
+/// is `None`, e.g. firmware on machines without EFI, or everything but
+/// userspace for the user manager.
+#[derive(Debug, Clone, Default)]
+pub struct BootTimes {
+    pub firmware: Option<u64>,
+    pub loader: Option<u64>,
+    pub kernel: Option<u64>,
+    pub initrd: Option<u64>,
+    /// `None` while the boot is still in progress.
+    pub userspace: Option<u64>,
+}
+
+impl BootTimes {
+    pub fn total(&self) -> u64 {
+        [
+            self.firmware,
+            self.loader,
+            self.kernel,
+            self.initrd,
+            self.userspace,
+        ]
+        .into_iter()
+        .flatten()
+        .sum()
+    }
+}
+
+/// Activation of one unit during boot.
+#[derive(Debug, Clone)]
+pub struct UnitTiming {
+    pub name: String,
+    /// When the unit left the inactive state.
+    pub activating: u64,
+    /// When the unit became active.
+    pub activated: u64,
+}
+
+impl UnitTiming {
+    /// Time spent activating, the number `systemd-analyze blame` shows.
+    pub fn duration(&self) -> u64 {
+        self.activated.saturating_sub(self.activating)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BootAnalysis {
+    pub times: BootTimes,
+    /// Units that were activated, slowest first.
+    pub blame: Vec<UnitTiming>,
+    /// From the default target down to the first unit of the chain. Every
+    /// unit is the one its predecessor waited for longest.
+    pub critical_chain: Vec<UnitTiming>,
+    pub default_target: String,
+}
+
+struct UnitRecord {
+    timing: UnitTiming,
+    after: Vec<String>,
+}
+
+impl SystemdServiceManager {
+    pub fn analyze_boot(&self, scope: UnitScope) -> Result<BootAnalysis> {
+        let conn = self.get_connection(scope)?;
+        let manager = self.get_manager_proxy(&conn)?;
+        let timestamp = |name: &str| manager.get_property::<u64>(name).unwrap_or(0);
+        let firmware = timestamp("FirmwareTimestampMonotonic");
+        let loader = timestamp("LoaderTimestampMonotonic");
+        let initrd = timestamp("InitRDTimestampMonotonic");
+        let userspace = timestamp("UserspaceTimestampMonotonic");
+        let finish = timestamp("FinishTimestampMonotonic");
+        let nonzero = |usec: u64| (usec > 0).then_some(usec);
+        let kernel_end = if initrd > 0 { initrd } else { userspace };
+        let times = BootTimes {
+            firmware: nonzero(firmware.saturating_sub(loader)),
+            loader: nonzero(loader),
+            kernel: (scope == UnitScope::System).then_some(kernel_end),
+            initrd: (initrd > 0).then(|| userspace.saturating_sub(initrd)),
+            userspace: (finish > 0).then(|| finish.saturating_sub(userspace)),
+        };
+        let default_target: String = manager.call("GetDefaultTarget", &())?;
+
+        let records: HashMap<String, UnitRecord> = self
+            .call_list_units(&conn)?
+            .into_par_iter()
+            .filter_map(|unit| {
+                let properties = self
+                    .get_all_properties(&conn, &unit.path, UNIT_INTERFACE)
+                    .ok()?;
+                Some((
+                    unit.name.clone(),
+                    UnitRecord::from_properties(unit.name, &properties),
+                ))
+            })
+            .collect();
+
+        let mut blame: Vec<UnitTiming> = records
+            .values()
+            .map(|record| &record.timing)
+            .filter(|timing| timing.activating >= userspace && timing.activated > 0)
+            .cloned()
+            .collect();
+        blame.sort_by(|a, b| b.duration().cmp(&a.duration()).then(a.name.cmp(&b.name)));
+
+        Ok(BootAnalysis {
+            times,
+            blame,
+            critical_chain: critical_chain(&records, &default_target),
+            default_target,
+        })
+    }
+}
+
+impl UnitRecord {
+    fn from_properties(name: String, unit: &Properties) -> Self {
+        let timestamp = |key| property::<u64>(unit, key).unwrap_or(0);
+        Self {
+            timing: UnitTiming {
+                name,
+                activating: timestamp("InactiveExitTimestampMonotonic"),
+                activated: timestamp("ActiveEnterTimestampMonotonic"),
+            },
+            after: property(unit, "After").unwrap_or_default(),
+        }
+    }
+}
+
+/// Follows `After=` from `root`, each time to the dependency that became
+/// active last before the unit itself started, like `systemd-analyze
+/// critical-chain` does.
+fn critical_chain(records: &HashMap<String, UnitRecord>, root: &str) -> Vec<UnitTiming> {
+    let mut chain = Vec::new();
+    let mut seen = HashSet::new();
+    let mut current = records.get(root);
+    while let Some(record) = current {
+        if !seen.insert(record.timing.name.as_str()) || record.timing.activated == 0 {
+            break;
+        }
+        chain.push(record.timing.clone());
+        let started = match record.timing.activating {
+            0 => record.timing.activated,
+            activating => activating,
+        };
+        current = record
+            .after
+            .iter()
+            .filter_map(|name| records.get(name))
+            .filter(|dep| dep.timing.activated > 0 && dep.timing.activated <= started)
+            .max_by_key(|dep| dep.timing.activated);
+    }
+    chain
+}
@@ -0,0 +1,524 @@
+//! Dependencies between the loaded units of a manager.
+//!
+//! Every unit lists its dependencies in properties of the
+//! `org.freedesktop.systemd1.Unit` interface, both forward (`Requires=`) and
+//! reverse (`RequiredBy`). The graph merges both sides, so an edge shows up
+//! even if only one end reports it.
+
+use super::{Properties, Result, SystemdServiceManager, UnitScope, property};
+use rayon::prelude::*;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt::Write;
+
+const UNIT_INTERFACE: &str = "org.freedesktop.systemd1.Unit";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DependencyKind {
+    Requires,
+    BindsTo,
+    PartOf,
+    Wants,
+    After,
+    RequiredBy,
+    BoundBy,
+    ConsistsOf,
+    WantedBy,
+    Before,
+}
+impl DependencyKind {
+    /// The kinds a unit declares itself; every other kind is the inverse of
+    /// one of these.
+    pub const FORWARD: [Self; 5] = [
+        Self::Requires,
+        Self::BindsTo,
+        Self::PartOf,
+        Self::Wants,
+        Self::After,
+    ];
+
+    pub const ALL: [Self; 10] = [
+        Self::Requires,
+        Self::BindsTo,
+        Self::PartOf,
+        Self::Wants,
+        Self::After,
+        Self::RequiredBy,
+        Self::BoundBy,
+        Self::ConsistsOf,
+        Self::WantedBy,
+        Self::Before,
+    ];
+
+    /// The unit property holding this kind of dependency.
+    pub fn property(&self) -> &'static str {
+        match self {
+            Self::Requires => "Requires",
+            Self::BindsTo => "BindsTo",
+            Self::PartOf => "PartOf",
+            Self::Wants => "Wants",
+            Self::After => "After",
+            Self::RequiredBy => "RequiredBy",
+            Self::BoundBy => "BoundBy",
+            Self::ConsistsOf => "ConsistsOf",
+            Self::WantedBy => "WantedBy",
+            Self::Before => "Before",
+        }
+    }
+
+    /// The same edge as seen from the other unit.
+    pub fn inverse(&self) -> Self {
+        match self {
+            Self::Requires => Self::RequiredBy,
+            Self::BindsTo => Self::BoundBy,
+            Self::PartOf => Self::ConsistsOf,
+            Self::Wants => Self::WantedBy,
+            Self::After => Self::Before,
+            Self::RequiredBy => Self::Requires,
+            Self::BoundBy => Self::BindsTo,
+            Self::ConsistsOf => Self::PartOf,
+            Self::WantedBy => Self::Wants,
+            Self::Before => Self::After,
+        }
+    }
+
+    pub fn direction(&self) -> Direction {
+        match self {
+            Self::Requires | Self::BindsTo | Self::PartOf | Self::Wants | Self::After => {
+                Direction::Forward
+            }
+            _ => Direction::Reverse,
+        }
+    }
+
+    /// Edge colours used in DOT output. Requires, Wants and After match
+    /// `systemd-analyze dot`; it draws no BindsTo or PartOf edges, and its
+    /// darkblue stands for Requisite, so those two get colours of their own.
+    fn dot_color(&self) -> &'static str {
+        match self {
+            Self::Requires | Self::RequiredBy => "black",
+            Self::BindsTo | Self::BoundBy => "orange",
+            Self::PartOf | Self::ConsistsOf => "purple",
+            Self::Wants | Self::WantedBy => "grey66",
+            Self::After | Self::Before => "green",
+        }
+    }
+
+    /// Whether stopping a unit also stops the units on the other end of
+    /// this edge.
+    fn propagates_stop(&self) -> bool {
+        matches!(self, Self::RequiredBy | Self::BoundBy | Self::ConsistsOf)
+    }
+}
+
+/// Output formats of [`DependencyGraph::export`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Graphviz, as written by `systemd-analyze dot`.
+    Dot,
+    /// `{"root": …, "units": {"a.service": {"active_state": …, "Requires":
+    /// [...], …}}}`, listing every unit that takes part in an edge.
+    Json,
+}
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Dot => "dot",
+            Self::Json => "json",
+        }
+    }
+}
+
+/// Forward edges point at what a unit pulls in or waits for, reverse edges
+/// at the units that pull it in or wait for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Forward,
+    Reverse,
+}
+
+#[derive(Debug, Default)]
+pub struct DependencyGraph {
+    edges: HashMap<String, BTreeSet<(DependencyKind, String)>>,
+    active_states: HashMap<String, String>,
+}
+
+impl DependencyGraph {
+    fn add_unit(&mut self, name: &str, unit: &Properties) {
+        if let Some(state) = property::<String>(unit, "ActiveState") {
+            self.active_states.insert(name.to_string(), state);
+        }
+        for kind in DependencyKind::ALL {
+            for other in property::<Vec<String>>(unit, kind.property()).unwrap_or_default() {
+                self.edges
+                    .entry(other.clone())
+                    .or_default()
+                    .insert((kind.inverse(), name.to_string()));
+                self.edges
+                    .entry(name.to_string())
+                    .or_default()
+                    .insert((kind, other));
+            }
+        }
+    }
+
+    /// The edges of `unit` in one direction, ordered by kind and name.
+    pub fn dependencies(&self, unit: &str, direction: Direction) -> Vec<(DependencyKind, &str)> {
+        self.edges
+            .get(unit)
+            .into_iter()
+            .flatten()
+            .filter(|(kind, _)| kind.direction() == direction)
+            .map(|(kind, other)| (*kind, other.as_str()))
+            .collect()
+    }
+
+    /// `None` for units that are referenced but not loaded.
+    pub fn active_state(&self, unit: &str) -> Option<&str> {
+        self.active_states.get(unit).map(String::as_str)
+    }
+
+    /// The forward edges of the given kinds, sorted. With a root only the
+    /// edges reachable from it are returned, otherwise all of them.
+    pub fn edges(
+        &self,
+        root: Option<&str>,
+        kinds: &[DependencyKind],
+    ) -> Vec<(&str, DependencyKind, &str)> {
+        let edges_of = |unit: &str| {
+            self.dependencies(unit, Direction::Forward)
+                .into_iter()
+                .filter(|(kind, _)| kinds.contains(kind))
+        };
+        let mut edges: Vec<(&str, DependencyKind, &str)> = match root {
+            None => self
+                .edges
+                .keys()
+                .flat_map(|unit| {
+                    edges_of(unit).map(move |(kind, other)| (unit.as_str(), kind, other))
+                })
+                .collect(),
+            Some(root) => {
+                let mut edges = Vec::new();
+                let mut seen = HashSet::from([root]);
+                let mut queue = VecDeque::from([root]);
+                while let Some(unit) = queue.pop_front() {
+                    let Some((unit, _)) = self.edges.get_key_value(unit) else {
+                        continue;
+                    };
+                    for (kind, other) in edges_of(unit) {
+                        edges.push((unit.as_str(), kind, other));
+                        if seen.insert(other) {
+                            queue.push_back(other);
+                        }
+                    }
+                }
+                edges
+            }
+        };
+        edges.sort();
+        edges
+    }
+
+    /// Writes the edges selected as in [`Self::edges`] in the given format.
+    pub fn export(
+        &self,
+        format: ExportFormat,
+        root: Option<&str>,
+        kinds: &[DependencyKind],
+    ) -> String {
+        let edges = self.edges(root, kinds);
+        match format {
+            ExportFormat::Dot => {
+                let mut dot = String::from("digraph systemd {\n");
+                for (unit, kind, other) in edges {
+                    let _ = writeln!(
+                        dot,
+                        "\t{}->{} [color=\"{}\"];",
+                        json_string(unit),
+                        json_string(other),
+                        kind.dot_color()
+                    );
+                }
+                dot.push_str("}\n");
+                dot
+            }
+            ExportFormat::Json => {
+                let mut units: BTreeMap<&str, BTreeMap<&str, Vec<&str>>> = BTreeMap::new();
+                for (unit, kind, other) in edges {
+                    units.entry(other).or_default();
+                    units
+                        .entry(unit)
+                        .or_default()
+                        .entry(kind.property())
+                        .or_default()
+                        .push(other);
+                }
+                let mut json = format!(
+                    "{{\n  \"root\": {},\n  \"units\": {{",
+                    root.map_or("null".to_string(), json_string)
+                );
+                for (i, (unit, unit_edges)) in units.iter().enumerate() {
+                    let _ = write!(
+                        json,
+                        "{}\n    {}: {{\"active_state\": {}",
+                        if i == 0 { "" } else { "," },
+                        json_string(unit),
+                        self.active_state(unit)
+                            .map_or("null".to_string(), json_string)
+                    );
+                    for (kind, others) in unit_edges {
+                        let others: Vec<String> = others.iter().map(|o| json_string(o)).collect();
+                        let _ = write!(json, ", \"{}\": [{}]", kind, others.join(", "));
+                    }
+                    json.push('}');
+                }
+                json.push_str("\n  }\n}\n");
+                json
+            }
+        }
+    }
+}
+
+/// Quotes a string for JSON. Unit names are ASCII in practice, so this also
+/// serves as a DOT identifier.
+fn json_string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(quoted, "\\u{:04x}", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+impl SystemdServiceManager {
+    /// Reads the dependencies of every loaded unit of one manager.
+    pub fn dependency_graph(&self, scope: UnitScope) -> Result<DependencyGraph> {
+        let conn = self.get_connection(scope)?;
+        let units: Vec<(String, Properties)> = self
+            .call_list_units(&conn)?
+            .into_par_iter()
+            .filter_map(|unit| {
+                let properties = self
+                    .get_all_properties(&conn, &unit.path, UNIT_INTERFACE)
+                    .ok()?;
+                Some((unit.name, properties))
+            })
+            .collect();
+
+        let mut graph = DependencyGraph::default();
+        for (name, properties) in &units {
+            graph.add_unit(name, properties);
+        }
+        Ok(graph)
+    }
+
+    /// Active units that systemd stops along with `unit`, directly or
+    /// through a chain of `Requires=`, `BindsTo=` or `PartOf=`. Only those
+    /// units are read, so this is much cheaper than the full graph. Units
+    /// that cannot be read are skipped, as in [`Self::dependency_graph`].
+    pub fn active_dependents(&self, unit: &str, scope: UnitScope) -> Result<Vec<String>> {
+        let conn = self.get_connection(scope)?;
+        let mut seen = HashSet::from([unit.to_string()]);
+        let mut queue = VecDeque::from([unit.to_string()]);
+        let mut dependents = Vec::new();
+
+        while let Some(name) = queue.pop_front() {
+            let Ok(path) = self.get_unit_path(&conn, &name) else {
+                continue;
+            };
+            let Ok(properties) = self.get_all_properties(&conn, &path, UNIT_INTERFACE) else {
+                continue;
+            };
+            if name != unit
+                && property::<String>(&properties, "ActiveState").as_deref() == Some("active")
+            {
+                dependents.push(name.clone());
+            }
+            for kind in DependencyKind::ALL.iter().filter(|k| k.propagates_stop()) {
+                for other in
+                    property::<Vec<String>>(&properties, kind.property()).unwrap_or_default()
+                {
+                    if seen.insert(other.clone()) {
+                        queue.push_back(other);
+                    }
+                }
+            }
+        }
+        dependents.sort();
+        Ok(dependents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zbus::zvariant::{OwnedValue, Value};
+
+    fn unit(active_state: &str, dependencies: &[(DependencyKind, &[&str])]) -> Properties {
+        let mut properties = Properties::new();
+        let value = |value: Value| OwnedValue::try_from(value).expect("plain value");
+        properties.insert("ActiveState".to_string(), value(Value::from(active_state)));
+        for (kind, others) in dependencies {
+            properties.insert(
+                kind.property().to_string(),
+                value(Value::from(others.to_vec())),
+            );
+        }
+        properties
+    }
+
+    /// a Requires b, b Wants c and c is After a, a cycle. d only reports its
+    /// `WantedBy=a`, and x requires y, which is not loaded.
+    fn graph() -> DependencyGraph {
+        let mut graph = DependencyGraph::default();
+        for (name, properties) in [
+            (
+                "a.service",
+                unit("active", &[(DependencyKind::Requires, &["b.service"])]),
+            ),
+            (
+                "b.service",
+                unit("active", &[(DependencyKind::Wants, &["c.service"])]),
+            ),
+            (
+                "c.service",
+                unit("inactive", &[(DependencyKind::After, &["a.service"])]),
+            ),
+            (
+                "d.service",
+                unit("active", &[(DependencyKind::WantedBy, &["a.service"])]),
+            ),
+            (
+                "x.service",
+                unit("failed", &[(DependencyKind::Requires, &["y.service"])]),
+            ),
+        ] {
+            graph.add_unit(name, &properties);
+        }
+        graph
+    }
+
+    #[test]
+    fn edges_without_root_lists_every_forward_edge() {
+        let graph = graph();
+        assert_eq!(
+            graph.edges(None, &DependencyKind::FORWARD),
+            vec![
+                ("a.service", DependencyKind::Requires, "b.service"),
+                ("a.service", DependencyKind::Wants, "d.service"),
+                ("b.service", DependencyKind::Wants, "c.service"),
+                ("c.service", DependencyKind::After, "a.service"),
+                ("x.service", DependencyKind::Requires, "y.service"),
+            ]
+        );
+    }
+
+    #[test]
+    fn edges_from_root_follow_a_cycle_once() {
+        let graph = graph();
+        assert_eq!(
+            graph.edges(Some("b.service"), &DependencyKind::FORWARD),
+            vec![
+                ("a.service", DependencyKind::Requires, "b.service"),
+                ("a.service", DependencyKind::Wants, "d.service"),
+                ("b.service", DependencyKind::Wants, "c.service"),
+                ("c.service", DependencyKind::After, "a.service"),
+            ]
+        );
+        assert!(
+            graph
+                .edges(Some("y.service"), &DependencyKind::FORWARD)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn edges_keep_only_the_given_kinds() {
+        let graph = graph();
+        assert_eq!(
+            graph.edges(None, &[DependencyKind::Requires]),
+            vec![
+                ("a.service", DependencyKind::Requires, "b.service"),
+                ("x.service", DependencyKind::Requires, "y.service"),
+            ]
+        );
+        // The After edge back to a is filtered out, so the walk ends at c.
+        assert_eq!(
+            graph.edges(
+                Some("b.service"),
+                &[DependencyKind::Requires, DependencyKind::Wants]
+            ),
+            vec![("b.service", DependencyKind::Wants, "c.service")]
+        );
+    }
+
+    #[test]
+    fn export_writes_dot() {
+        let graph = graph();
+        assert_eq!(
+            graph.export(
+                ExportFormat::Dot,
+                Some("c.service"),
+                &DependencyKind::FORWARD
+            ),
+            "digraph systemd {\n\
+             \t\"a.service\"->\"b.service\" [color=\"black\"];\n\
+             \t\"a.service\"->\"d.service\" [color=\"grey66\"];\n\
+             \t\"b.service\"->\"c.service\" [color=\"grey66\"];\n\
+             \t\"c.service\"->\"a.service\" [color=\"green\"];\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn export_writes_json() {
+        let graph = graph();
+        assert_eq!(
+            graph.export(ExportFormat::Json, None, &[DependencyKind::Requires]),
+            r#"{
+  "root": null,
+  "units": {
+    "a.service": {"active_state": "active", "Requires": ["b.service"]},
+    "b.service": {"active_state": "active"},
+    "x.service": {"active_state": "failed", "Requires": ["y.service"]},
+    "y.service": {"active_state": null}
+  }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn export_escapes_unit_names() {
+        let mut graph = DependencyGraph::default();
+        graph.add_unit(
+            r#"we"ird\x2d.service"#,
+            &unit("active", &[(DependencyKind::Requires, &["a.service"])]),
+        );
+        let root = r#"we"ird\x2d.service"#;
+        assert_eq!(
+            graph.export(ExportFormat::Dot, Some(root), &[DependencyKind::Requires]),
+            "digraph systemd {\n\t\"we\\\"ird\\\\x2d.service\"->\"a.service\" [color=\"black\"];\n}\n"
+        );
+        assert_eq!(
+            graph.export(ExportFormat::Json, Some(root), &[DependencyKind::Requires]),
+            r#"{
+  "root": "we\"ird\\x2d.service",
+  "units": {
+    "a.service": {"active_state": null},
+    "we\"ird\\x2d.service": {"active_state": "active", "Requires": ["a.service"]}
+  }
+}
+"#
+        );
+        assert_eq!(json_string("tab\there\u{1}"), r#""tab\u0009here\u0001""#);
+    }
+}
@@ -0,0 +1,249 @@
+//! Live updates from the systemd managers.
+//!
+//! After `Subscribe`, the manager broadcasts `UnitNew`, `UnitRemoved`,
+//! `JobNew`/`JobRemoved` and `PropertiesChanged` for its unit objects. Each
+//! bus is watched on its own thread and the changes are sent to the caller as
+//! [`ServiceEvent`]s.
+
+use super::{
+    EnablementStatus, Properties, Result, SystemdServiceManager, UnitInfo, UnitScope, UnitType,
+    UnitTypeInfo, property, templates,
+};
+use std::sync::mpsc::{self, Receiver, Sender};
+use zbus::blocking::{Connection, MessageIterator};
+use zbus::message::Type as MessageType;
+use zbus::zvariant::OwnedObjectPath;
+use zbus::{MatchRule, Message};
+
+const MANAGER_PATH: &str = "/org/freedesktop/systemd1";
+const MANAGER_INTERFACE: &str = "org.freedesktop.systemd1.Manager";
+const UNIT_INTERFACE: &str = "org.freedesktop.systemd1.Unit";
+const JOB_INTERFACE: &str = "org.freedesktop.systemd1.Job";
+const PROPERTIES_INTERFACE: &str = "org.freedesktop.DBus.Properties";
+
+#[derive(Debug, Clone)]
+pub enum ServiceEvent {
+    /// A unit appeared or one of its states changed.
+    Changed(Box<UnitInfo>),
+    /// A unit was unloaded by its manager and has no unit file.
+    Removed { name: String, scope: UnitScope },
+    /// The manager reloaded or its unit files changed, so every unit of the
+    /// scope was re-read. Units missing from the list are gone.
+    Resync {
+        scope: UnitScope,
+        services: Vec<UnitInfo>,
+    },
+    /// A job was queued or finished, for a unit of any type.
+    JobsChanged,
+    /// The manager of the scope is no longer watched, so its rows only
+    /// change on a manual refresh.
+    WatchStopped { scope: UnitScope, reason: String },
+}
+
+/// Why [`SystemdServiceManager::watch_manager`] returned without an error.
+enum Watch {
+    ReceiverDropped,
+    BusClosed,
+}
+
+impl UnitInfo {
+    /// Builds the list entry from the `org.freedesktop.systemd1.Unit`
+    /// properties of a loaded unit. The job type lives on the job object and
+    /// the type info on the type's own interface, so both are passed in.
+    fn from_unit_properties(
+        unit: &Properties,
+        unit_type: UnitType,
+        job_type: Option<String>,
+        type_info: Option<UnitTypeInfo>,
+        scope: UnitScope,
+    ) -> Self {
+        let string = |key| property::<String>(unit, key).unwrap_or_default();
+        Self {
+            name: string("Id"),
+            unit_type,
+            description: string("Description"),
+            status: string("ActiveState").as_str().into(),
+            enablement_status: match string("UnitFileState").as_str() {
+                "" => EnablementStatus::Unknown("unknown".to_string()),
+                state => state.into(),
+            },
+            scope,
+            sub_state: string("SubState"),
+            load_state: string("LoadState"),
+            job: property::<(u32, OwnedObjectPath)>(unit, "Job")
+                .filter(|(id, _)| *id != 0)
+                .map(|(id, _)| (id, job_type.unwrap_or_default())),
+            type_info,
+        }
+    }
+}
+
+impl SystemdServiceManager {
+    /// Starts watching both managers. Events arrive on the returned channel
+    /// until the receiver is dropped. A bus that is not reachable, or that
+    /// goes away, ends with [`ServiceEvent::WatchStopped`].
+    pub fn subscribe(&self) -> Receiver<ServiceEvent> {
+        let (sender, receiver) = mpsc::channel();
+        for scope in [UnitScope::System, UnitScope::User] {
+            let manager = self.clone();
+            let sender = sender.clone();
+            std::thread::spawn(move || {
+                let reason = match manager.watch_manager(scope, &sender) {
+                    Ok(Watch::ReceiverDropped) => return,
+                    Ok(Watch::BusClosed) => "the bus connection was closed".to_string(),
+                    Err(e) => e.to_string(),
+                };
+                let _ = sender.send(ServiceEvent::WatchStopped { scope, reason });
+            });
+        }
+        receiver
+    }
+
+    fn watch_manager(&self, scope: UnitScope, sender: &Sender<ServiceEvent>) -> Result<Watch> {
+        let conn = self.get_connection(scope)?;
+        let rule = MatchRule::builder()
+            .msg_type(MessageType::Signal)
+            .path_namespace(MANAGER_PATH)?
+            .build();
+        let messages = MessageIterator::for_match_rule(rule, &conn, None)?;
+        self.get_manager_proxy(&conn)?
+            .call_method("Subscribe", &())?;
+
+        for message in messages {
+            let message = message?;
+            let jobs_changed = is_job_signal(&message).then_some(ServiceEvent::JobsChanged);
+            let events = jobs_changed
+                .into_iter()
+                .chain(self.event_for_signal(&conn, &message, scope));
+            for event in events {
+                if sender.send(event).is_err() {
+                    return Ok(Watch::ReceiverDropped);
+                }
+            }
+        }
+        Ok(Watch::BusClosed)
+    }
+
+    /// Maps one signal to an event. Anything that does not concern a unit of
+    /// a known type, or whose unit vanished in the meantime, is dropped.
+    fn event_for_signal(
+        &self,
+        conn: &Connection,
+        message: &Message,
+        scope: UnitScope,
+    ) -> Option<ServiceEvent> {
+        let header = message.header();
+        let interface = header.interface()?.as_str();
+        let member = header.member()?.as_str();
+        let body = message.body();
+
+        match (interface, member) {
+            (MANAGER_INTERFACE, "UnitNew") => {
+                let (name, path): (String, OwnedObjectPath) = body.deserialize().ok()?;
+                UnitType::from_name(&name)?;
+                self.changed_event(conn, &path, scope)
+            }
+            (MANAGER_INTERFACE, "UnitRemoved") => {
+                let (name, _): (String, OwnedObjectPath) = body.deserialize().ok()?;
+                let unit_type = UnitType::from_name(&name)?;
+                // An unloaded unit that still has a unit file stays listed.
+                // Instances report their template's file, but are only
+                // listed while loaded.
+                let unit_file_state = match templates::template_of(&name) {
+                    Some(_) => None,
+                    None => self.get_unit_file_state(conn, &name).ok(),
+                };
+                match unit_file_state {
+                    Some(state) => Some(ServiceEvent::Changed(Box::new(UnitInfo::not_loaded(
+                        &name,
+                        unit_type,
+                        state.as_str().into(),
+                        scope,
+                    )))),
+                    None => Some(ServiceEvent::Removed { name, scope }),
+                }
+            }
+            (MANAGER_INTERFACE, "JobNew") => {
+                let (_, _, name): (u32, OwnedObjectPath, String) = body.deserialize().ok()?;
+                self.unit_changed_event(conn, &name, scope)
+            }
+            (MANAGER_INTERFACE, "JobRemoved") => {
+                let (_, _, name, _): (u32, OwnedObjectPath, String, String) =
+                    body.deserialize().ok()?;
+                self.unit_changed_event(conn, &name, scope)
+            }
+            (MANAGER_INTERFACE, "UnitFilesChanged") => self.resync_event(conn, scope),
+            (MANAGER_INTERFACE, "Reloading") => {
+                let (active,): (bool,) = body.deserialize().ok()?;
+                (!active).then(|| self.resync_event(conn, scope))?
+            }
+            (PROPERTIES_INTERFACE, "PropertiesChanged") => {
+                let (changed_interface, ..): (String, Properties, Vec<String>) =
+                    body.deserialize().ok()?;
+                let tracked = changed_interface == UNIT_INTERFACE
+                    || UnitType::ALL
+                        .iter()
+                        .any(|t| t.info_interface() == Some(changed_interface.as_str()));
+                if !tracked {
+                    return None;
+                }
+                let path = OwnedObjectPath::from(header.path()?.to_owned());
+                self.changed_event(conn, &path, scope)
+            }
+            _ => None,
+        }
+    }
+
+    fn get_unit_file_state(&self, conn: &Connection, name: &str) -> Result<String> {
+        self.get_manager_proxy(conn)?
+            .call("GetUnitFileState", &(name,))
+            .map_err(Into::into)
+    }
+
+    fn changed_event(
+        &self,
+        conn: &Connection,
+        path: &OwnedObjectPath,
+        scope: UnitScope,
+    ) -> Option<ServiceEvent> {
+        let properties = self.get_all_properties(conn, path, UNIT_INTERFACE).ok()?;
+        let job_type = property::<(u32, OwnedObjectPath)>(&properties, "Job")
+            .filter(|(id, _)| *id != 0)
+            .and_then(|(_, job_path)| {
+                self.get_unit_proxy(conn, job_path, JOB_INTERFACE)
+                    .ok()?
+                    .get_property::<String>("JobType")
+                    .ok()
+            });
+        let name = property::<String>(&properties, "Id")?;
+        let unit_type = UnitType::from_name(&name)?;
+        let type_info = self.get_type_info(conn, path, unit_type);
+        let unit =
+            UnitInfo::from_unit_properties(&properties, unit_type, job_type, type_info, scope);
+        Some(ServiceEvent::Changed(Box::new(unit)))
+    }
+
+    fn unit_changed_event(
+        &self,
+        conn: &Connection,
+        name: &str,
+        scope: UnitScope,
+    ) -> Option<ServiceEvent> {
+        UnitType::from_name(name)?;
+        let path = self.get_unit_path(conn, name).ok()?;
+        self.changed_event(conn, &path, scope)
+    }
+
+    fn resync_event(&self, conn: &Connection, scope: UnitScope) -> Option<ServiceEvent> {
+        let services = self.fetch_units(Ok(conn.clone()), scope).ok()?;
+        Some(ServiceEvent::Resync { scope, services })
+    }
+}
+
+fn is_job_signal(message: &Message) -> bool {
+    let header = message.header();
+    header.interface().is_some_and(|i| i == MANAGER_INTERFACE)
+        && header
+            .member()
+            .is_some_and(|m| m == "JobNew" || m == "JobRemoved")
+}
@@ -0,0 +1,136 @@
+//! Units in the `failed` state and why they got there.
+
+use super::unitfile::type_section;
+use super::{
+    Properties, Result, ServiceError, SystemdServiceManager, UnitScope, UnitType, property,
+};
+use rayon::prelude::*;
+
+const UNIT_INTERFACE: &str = "org.freedesktop.systemd1.Unit";
+
+/// `ExecMainCode` values, the `si_code` of the main process's exit.
+const CLD_EXITED: i32 = 1;
+const CLD_KILLED: i32 = 2;
+const CLD_DUMPED: i32 = 3;
+
+/// How the main process of a failed service ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MainExit {
+    /// Exited on its own with this status.
+    Exited(i32),
+    /// Terminated by this signal.
+    Killed(i32),
+    /// Terminated by this signal and dumped core.
+    Dumped(i32),
+}
+
+/// The failed units of the managers that could be read, and why the others
+/// could not.
+#[derive(Debug)]
+pub struct FailedUnits {
+    pub units: Vec<FailedUnit>,
+    pub unreachable: Vec<(UnitScope, ServiceError)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FailedUnit {
+    pub name: String,
+    pub scope: UnitScope,
+    pub description: String,
+    /// The type's `Result` property, e.g. `exit-code`, `signal`, `timeout`,
+    /// `core-dump` or `start-limit-hit`. Empty for types without one.
+    pub result: String,
+    /// Only known for services whose main process ran.
+    pub main_exit: Option<MainExit>,
+    /// When the unit entered the failed state, wall clock µs.
+    pub failed_at: u64,
+}
+
+impl FailedUnit {
+    fn new(
+        name: String,
+        description: String,
+        scope: UnitScope,
+        unit: &Properties,
+        type_properties: Option<&Properties>,
+    ) -> Self {
+        let type_property = |key| type_properties.and_then(|p| property::<i32>(p, key));
+        let main_exit = match (
+            type_property("ExecMainCode"),
+            type_property("ExecMainStatus"),
+        ) {
+            (Some(CLD_EXITED), Some(status)) => Some(MainExit::Exited(status)),
+            (Some(CLD_KILLED), Some(signal)) => Some(MainExit::Killed(signal)),
+            (Some(CLD_DUMPED), Some(signal)) => Some(MainExit::Dumped(signal)),
+            _ => None,
+        };
+        Self {
+            name,
+            scope,
+            description,
+            result: type_properties
+                .and_then(|p| property(p, "Result"))
+                .unwrap_or_default(),
+            main_exit,
+            failed_at: property(unit, "StateChangeTimestamp").unwrap_or(0),
+        }
+    }
+}
+
+impl SystemdServiceManager {
+    /// Lists the failed units of both managers, most recent failure first.
+    /// A manager that cannot be read is reported in
+    /// [`FailedUnits::unreachable`]; only if neither can is this an error.
+    pub fn list_failed_units(&self) -> Result<FailedUnits> {
+        let (system, user) = rayon::join(
+            || self.list_scope_failed_units(UnitScope::System),
+            || self.list_scope_failed_units(UnitScope::User),
+        );
+        let mut failed = FailedUnits {
+            units: Vec::new(),
+            unreachable: Vec::new(),
+        };
+        for (scope, result) in [(UnitScope::System, system), (UnitScope::User, user)] {
+            match result {
+                Ok(units) => failed.units.extend(units),
+                Err(e) => failed.unreachable.push((scope, e)),
+            }
+        }
+        if failed.unreachable.len() == 2 {
+            let (_, e) = failed.unreachable.remove(0);
+            return Err(e);
+        }
+        failed
+            .units
+            .sort_by(|a, b| b.failed_at.cmp(&a.failed_at).then(a.name.cmp(&b.name)));
+        Ok(failed)
+    }
+
+    fn list_scope_failed_units(&self, scope: UnitScope) -> Result<Vec<FailedUnit>> {
+        let conn = self.get_connection(scope)?;
+        let units = self
+            .call_list_units(&conn)?
+            .into_par_iter()
+            .filter(|unit| unit.active_state == "failed")
+            .filter_map(|unit| {
+                let properties = self
+                    .get_all_properties(&conn, &unit.path, UNIT_INTERFACE)
+                    .ok()?;
+                let type_properties = UnitType::from_name(&unit.name)
+                    .and_then(type_section)
+                    .and_then(|section| {
+                        let interface = format!("org.freedesktop.systemd1.{}", section);
+                        self.get_all_properties(&conn, &unit.path, &interface).ok()
+                    });
+                Some(FailedUnit::new(
+                    unit.name,
+                    unit.description,
+                    scope,
+                    &properties,
+                    type_properties.as_ref(),
+                ))
+            })
+            .collect();
+        Ok(units)
+    }
+}
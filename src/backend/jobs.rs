@@ -0,0 +1,190 @@
+//! Waiting for the jobs queued by `StartUnit` and friends.
+//!
+//! The Manager replies to `StartUnit` as soon as the job is queued. The real
+//! outcome only arrives later in a `JobRemoved` signal carrying the job path
+//! and a result string.
+
+use super::{Result, SystemdServiceManager, UNIT_ACTION_ID, UnitScope};
+use std::sync::mpsc;
+use std::time::Duration;
+use zbus::MatchRule;
+use zbus::blocking::{Connection, MessageIterator};
+use zbus::message::Type as MessageType;
+use zbus::zvariant::OwnedObjectPath;
+
+/// How long to wait for a job before reporting it as unfinished. This is a
+/// little above systemd's default `TimeoutStartSec=`.
+pub const JOB_WAIT_TIMEOUT: Duration = Duration::from_secs(100);
+
+/// The result string of `JobRemoved`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JobResult {
+    Done,
+    Canceled,
+    /// The job itself ran into its `JobTimeoutSec=`.
+    Timeout,
+    Failed,
+    Dependency,
+    Skipped,
+    /// The job was still running when we stopped waiting for it.
+    Unfinished,
+    Other(String),
+}
+impl From<&str> for JobResult {
+    fn from(s: &str) -> Self {
+        match s {
+            "done" => Self::Done,
+            "canceled" => Self::Canceled,
+            "timeout" => Self::Timeout,
+            "failed" => Self::Failed,
+            "dependency" => Self::Dependency,
+            "skipped" => Self::Skipped,
+            _ => Self::Other(s.to_string()),
+        }
+    }
+}
+impl JobResult {
+    /// Whether the unit ended up where the job wanted it. A skipped job had
+    /// nothing to do, e.g. a try-restart of a stopped unit.
+    pub fn is_success(&self) -> bool {
+        matches!(self, Self::Done | Self::Skipped)
+    }
+
+    pub fn describe(&self) -> &str {
+        match self {
+            Self::Done => "done",
+            Self::Canceled => "the job was canceled",
+            Self::Timeout => "the job timed out",
+            Self::Failed => "the unit failed",
+            Self::Dependency => "a dependency failed",
+            Self::Skipped => "nothing to do",
+            Self::Unfinished => "the job is still running",
+            Self::Other(result) => result,
+        }
+    }
+}
+
+/// An entry of the Manager's job queue.
+#[derive(Debug, Clone)]
+pub struct JobInfo {
+    pub id: u32,
+    pub unit: String,
+    pub job_type: String,
+    /// `waiting` or `running`.
+    pub state: String,
+    pub scope: UnitScope,
+}
+
+impl SystemdServiceManager {
+    /// Lists the queued jobs of both managers, skipping a bus that is not
+    /// reachable.
+    pub fn list_jobs(&self) -> Result<Vec<JobInfo>> {
+        let mut jobs = Vec::new();
+        for scope in [UnitScope::System, UnitScope::User] {
+            if let Ok(scope_jobs) = self.list_scope_jobs(scope) {
+                jobs.extend(scope_jobs);
+            }
+        }
+        Ok(jobs)
+    }
+
+    fn list_scope_jobs(&self, scope: UnitScope) -> Result<Vec<JobInfo>> {
+        let conn = self.get_connection(scope)?;
+        let jobs: Vec<(
+            u32,
+            String,
+            String,
+            String,
+            OwnedObjectPath,
+            OwnedObjectPath,
+        )> = self.get_manager_proxy(&conn)?.call("ListJobs", &())?;
+        Ok(jobs
+            .into_iter()
+            .map(|(id, unit, job_type, state, ..)| JobInfo {
+                id,
+                unit,
+                job_type,
+                state,
+                scope,
+            })
+            .collect())
+    }
+
+    pub fn cancel_job(&self, id: u32, scope: UnitScope) -> Result<()> {
+        let conn = self.get_scoped_connection(scope, UNIT_ACTION_ID)?;
+        self.get_manager_proxy(&conn)?
+            .call_method("CancelJob", &(id,))?;
+        Ok(())
+    }
+
+    /// Cancels every queued job of the manager.
+    pub fn clear_jobs(&self, scope: UnitScope) -> Result<()> {
+        let conn = self.get_scoped_connection(scope, UNIT_ACTION_ID)?;
+        self.get_manager_proxy(&conn)?
+            .call_method("ClearJobs", &())?;
+        Ok(())
+    }
+}
+
+/// Listens for `JobRemoved` on a connection of its own. It has to be created
+/// before the job is queued, otherwise a fast job can finish before we
+/// listen.
+pub struct JobWatcher {
+    conn: Connection,
+    receiver: mpsc::Receiver<(OwnedObjectPath, String)>,
+}
+impl JobWatcher {
+    pub fn new(manager: &SystemdServiceManager, scope: UnitScope) -> Result<Self> {
+        let conn = manager.get_connection(scope)?;
+        let rule = MatchRule::builder()
+            .msg_type(MessageType::Signal)
+            .path("/org/freedesktop/systemd1")?
+            .interface("org.freedesktop.systemd1.Manager")?
+            .member("JobRemoved")?
+            .build();
+        let messages = MessageIterator::for_match_rule(rule, &conn, None)?;
+        conn.call_method(
+            Some("org.freedesktop.systemd1"),
+            "/org/freedesktop/systemd1",
+            Some("org.freedesktop.systemd1.Manager"),
+            "Subscribe",
+            &(),
+        )?;
+
+        // The iterator blocks without a timeout, so it is drained on its own
+        // thread. Dropping the watcher closes the connection, which ends the
+        // iterator and with it the thread.
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            for message in messages.flatten() {
+                let Ok((_, job, _, result)) = message
+                    .body()
+                    .deserialize::<(u32, OwnedObjectPath, String, String)>()
+                else {
+                    continue;
+                };
+                if sender.send((job, result)).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self { conn, receiver })
+    }
+
+    pub fn wait(&self, job_path: &OwnedObjectPath, timeout: Duration) -> JobResult {
+        let deadline = std::time::Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+            match self.receiver.recv_timeout(remaining) {
+                Ok((job, result)) if job == *job_path => return result.as_str().into(),
+                Ok(_) => continue,
+                Err(_) => return JobResult::Unfinished,
+            }
+        }
+    }
+}
+impl Drop for JobWatcher {
+    fn drop(&mut self) {
+        let _ = self.conn.clone().close();
+    }
+}
@@ -0,0 +1,408 @@
+//! Journal access for a single unit.
+//!
+//! Entries are read from `journalctl --output=export`, so the parser works on
+//! any export stream, including files written with `journalctl -o export`.
+
+use super::{Result, ServiceError, UnitScope};
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+
+/// `MESSAGE_ID` systemd logs when a unit enters the failed state.
+const UNIT_FAILED_MESSAGE_ID: &str = "d9b373ed55a64feb8242e02dbe79a49c";
+
+/// Syslog priority of an entry, from `PRIORITY=`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    Emergency,
+    Alert,
+    Critical,
+    Error,
+    Warning,
+    Notice,
+    Info,
+    Debug,
+}
+impl Priority {
+    pub const ALL: [Priority; 8] = [
+        Self::Emergency,
+        Self::Alert,
+        Self::Critical,
+        Self::Error,
+        Self::Warning,
+        Self::Notice,
+        Self::Info,
+        Self::Debug,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Emergency => "Emergency",
+            Self::Alert => "Alert",
+            Self::Critical => "Critical",
+            Self::Error => "Error",
+            Self::Warning => "Warning",
+            Self::Notice => "Notice",
+            Self::Info => "Info",
+            Self::Debug => "Debug",
+        }
+    }
+
+    fn level(&self) -> u8 {
+        *self as u8
+    }
+}
+impl TryFrom<&str> for Priority {
+    type Error = ();
+
+    fn try_from(s: &str) -> std::result::Result<Self, ()> {
+        s.parse::<usize>()
+            .ok()
+            .and_then(|level| Self::ALL.get(level).copied())
+            .ok_or(())
+    }
+}
+
+/// A single journal entry with the fields the log view needs. `fields` keeps
+/// every text field of the entry for anything else.
+#[derive(Debug, Clone, Default)]
+pub struct JournalEntry {
+    /// Microseconds since the epoch.
+    pub realtime: u64,
+    pub priority: Option<Priority>,
+    pub identifier: String,
+    pub pid: Option<u32>,
+    pub message: String,
+    pub fields: HashMap<String, String>,
+}
+impl JournalEntry {
+    fn from_fields(fields: HashMap<String, String>) -> Self {
+        let get = |key: &str| fields.get(key).cloned().unwrap_or_default();
+        Self {
+            realtime: get("__REALTIME_TIMESTAMP").parse().unwrap_or(0),
+            priority: fields
+                .get("PRIORITY")
+                .and_then(|p| Priority::try_from(p.as_str()).ok()),
+            identifier: fields
+                .get("SYSLOG_IDENTIFIER")
+                .or_else(|| fields.get("_COMM"))
+                .cloned()
+                .unwrap_or_default(),
+            pid: fields.get("_PID").and_then(|p| p.parse().ok()),
+            message: get("MESSAGE"),
+            fields,
+        }
+    }
+
+    /// Errors and worse, plus systemd's own "unit entered failed state"
+    /// message, which is only logged as a warning.
+    pub fn is_failure(&self) -> bool {
+        self.priority.is_some_and(|p| p <= Priority::Error)
+            || self.fields.get("MESSAGE_ID").map(String::as_str) == Some(UNIT_FAILED_MESSAGE_ID)
+    }
+}
+
+/// Which entries to read for a unit.
+#[derive(Debug, Clone)]
+pub struct JournalQuery {
+    pub unit: String,
+    pub scope: UnitScope,
+    /// Least severe priority to include, like `journalctl --priority`.
+    pub max_priority: Priority,
+    /// Only entries at or after this realtime timestamp (µs since the epoch).
+    pub since: Option<u64>,
+    /// Number of most recent entries to read.
+    pub lines: usize,
+}
+impl JournalQuery {
+    pub fn new(unit: &str, scope: UnitScope) -> Self {
+        Self {
+            unit: unit.to_string(),
+            scope,
+            max_priority: Priority::Debug,
+            since: None,
+            lines: 1000,
+        }
+    }
+
+    /// The fields that tie an entry to the unit: the processes of the unit
+    /// itself and the messages its manager logs about it.
+    fn unit_fields(&self) -> [&'static str; 2] {
+        match self.scope {
+            UnitScope::System => ["_SYSTEMD_UNIT", "UNIT"],
+            UnitScope::User => ["_SYSTEMD_USER_UNIT", "USER_UNIT"],
+        }
+    }
+
+    /// Applies the query to an already parsed entry. `journalctl` does the
+    /// same filtering, this keeps export files and follow streams honest.
+    pub fn matches(&self, entry: &JournalEntry) -> bool {
+        let unit_matches = self
+            .unit_fields()
+            .iter()
+            .any(|field| entry.fields.get(*field) == Some(&self.unit));
+        // journalctl only keeps entries without PRIORITY when no priority
+        // filter applies, so anything stricter than Debug drops them.
+        let priority_matches = self.max_priority == Priority::Debug
+            || entry.priority.is_some_and(|p| p <= self.max_priority);
+        let since_matches = self.since.is_none_or(|since| entry.realtime >= since);
+        unit_matches && priority_matches && since_matches
+    }
+
+    fn journalctl_args(&self, follow: bool) -> Vec<String> {
+        let [process_field, manager_field] = self.unit_fields();
+        let mut args = vec![
+            "--output=export".to_string(),
+            "--no-pager".to_string(),
+            format!("--priority={}", self.max_priority.level()),
+            format!("--lines={}", if follow { 0 } else { self.lines }),
+        ];
+        if self.scope == UnitScope::User {
+            args.push("--user".to_string());
+        }
+        if let Some(since) = self.since {
+            args.push(format!("--since=@{}", since / 1_000_000));
+        }
+        if follow {
+            args.push("--follow".to_string());
+        }
+        args.push(format!("{}={}", process_field, self.unit));
+        args.push("+".to_string());
+        args.push(format!("{}={}", manager_field, self.unit));
+        args
+    }
+
+    fn spawn(&self, follow: bool) -> Result<Child> {
+        Command::new("journalctl")
+            .args(self.journalctl_args(follow))
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(ServiceError::from)
+    }
+}
+
+/// Reads the most recent entries matching `query`, oldest first.
+pub fn read_entries(query: &JournalQuery) -> Result<Vec<JournalEntry>> {
+    let mut child = query.spawn(false)?;
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let entries = ExportReader::new(BufReader::new(stdout))
+        .filter(|entry| entry.as_ref().map_or(true, |e| query.matches(e)))
+        .collect::<io::Result<Vec<_>>>();
+    let status = child.wait()?;
+    let entries = entries?;
+    if !status.success() && entries.is_empty() {
+        return Err(ServiceError::Journal(format!(
+            "journalctl exited with {}",
+            status
+        )));
+    }
+    Ok(entries)
+}
+
+/// A running `journalctl --follow`. New entries are parsed on a background
+/// thread; the process is killed when the follower is dropped.
+pub struct JournalFollower {
+    child: Child,
+    receiver: Receiver<JournalEntry>,
+}
+impl JournalFollower {
+    pub fn start(query: &JournalQuery) -> Result<Self> {
+        let mut child = query.spawn(true)?;
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let (sender, receiver) = mpsc::channel();
+        let query = query.clone();
+        std::thread::spawn(move || {
+            for entry in ExportReader::new(BufReader::new(stdout)) {
+                match entry {
+                    Ok(entry) if query.matches(&entry) => {
+                        if sender.send(entry).is_err() {
+                            break;
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(_) => break,
+                }
+            }
+        });
+        Ok(Self { child, receiver })
+    }
+
+    /// Entries that arrived since the last call, without blocking.
+    pub fn drain(&self) -> Vec<JournalEntry> {
+        self.receiver.try_iter().collect()
+    }
+}
+impl Drop for JournalFollower {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Iterator over the entries of a journal export stream.
+///
+/// Text fields are `KEY=value` lines. Binary fields are the key on its own
+/// line, a little-endian `u64` length, the data and a newline. Entries are
+/// separated by an empty line.
+pub struct ExportReader<R> {
+    reader: R,
+}
+impl<R: BufRead> ExportReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self { reader }
+    }
+
+    fn read_entry(&mut self) -> io::Result<Option<JournalEntry>> {
+        let mut fields = HashMap::new();
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            if self.reader.read_until(b'\n', &mut line)? == 0 {
+                break;
+            }
+            if line.last() == Some(&b'\n') {
+                line.pop();
+            }
+            if line.is_empty() {
+                if fields.is_empty() {
+                    continue;
+                }
+                break;
+            }
+            match line.iter().position(|b| *b == b'=') {
+                Some(eq) => {
+                    let key = String::from_utf8_lossy(&line[..eq]).into_owned();
+                    let value = String::from_utf8_lossy(&line[eq + 1..]).into_owned();
+                    fields.insert(key, value);
+                }
+                None => {
+                    let key = String::from_utf8_lossy(&line).into_owned();
+                    let mut len = [0u8; 8];
+                    self.reader.read_exact(&mut len)?;
+                    let mut data = vec![0u8; u64::from_le_bytes(len) as usize];
+                    self.reader.read_exact(&mut data)?;
+                    let mut newline = [0u8; 1];
+                    self.reader.read_exact(&mut newline)?;
+                    fields.insert(key, String::from_utf8_lossy(&data).into_owned());
+                }
+            }
+        }
+        Ok((!fields.is_empty()).then(|| JournalEntry::from_fields(fields)))
+    }
+}
+impl<R: BufRead> Iterator for ExportReader<R> {
+    type Item = io::Result<JournalEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_entry().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPORT: &[u8] = include_bytes!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/journal/demo.export"
+    ));
+
+    fn read_fixture() -> Vec<JournalEntry> {
+        ExportReader::new(EXPORT)
+            .collect::<io::Result<Vec<_>>>()
+            .expect("fixture parses")
+    }
+
+    #[test]
+    fn export_reader_yields_every_entry() {
+        let entries = read_fixture();
+        assert_eq!(entries.len(), 4);
+
+        let first = &entries[0];
+        assert_eq!(first.realtime, 1_700_000_000_000_000);
+        assert_eq!(first.priority, Some(Priority::Info));
+        assert_eq!(first.identifier, "demo");
+        assert_eq!(first.pid, Some(412));
+        assert_eq!(first.message, "Listening on port 8080");
+        assert!(!first.is_failure());
+    }
+
+    #[test]
+    fn export_reader_reads_binary_fields() {
+        let entry = &read_fixture()[1];
+        assert_eq!(entry.message, "Traceback:\n  line 1\n  line 2");
+        assert_eq!(entry.priority, None);
+        assert_eq!(entry.pid, None);
+        assert_eq!(entry.identifier, "systemd");
+        assert_eq!(
+            entry.fields.get("UNIT").map(String::as_str),
+            Some("demo.service")
+        );
+    }
+
+    #[test]
+    fn failed_message_id_counts_as_failure() {
+        let entry = &read_fixture()[2];
+        assert_eq!(entry.priority, Some(Priority::Error));
+        assert!(entry.is_failure());
+    }
+
+    #[test]
+    fn matches_unit_priority_and_since() {
+        let entries = read_fixture();
+        let matching = |query: &JournalQuery| -> Vec<u64> {
+            entries
+                .iter()
+                .filter(|entry| query.matches(entry))
+                .map(|entry| entry.realtime)
+                .collect()
+        };
+
+        let mut query = JournalQuery::new("demo.service", UnitScope::System);
+        assert_eq!(
+            matching(&query),
+            [
+                1_700_000_000_000_000,
+                1_700_000_060_000_000,
+                1_700_000_120_000_000
+            ]
+        );
+
+        // Entries without a priority are dropped once a priority filter
+        // applies, like journalctl does.
+        query.max_priority = Priority::Warning;
+        assert_eq!(matching(&query), [1_700_000_120_000_000]);
+
+        query.since = Some(1_700_000_100_000_000);
+        assert_eq!(matching(&query), [1_700_000_120_000_000]);
+
+        let user_query = JournalQuery::new("demo.service", UnitScope::User);
+        assert!(matching(&user_query).is_empty());
+    }
+
+    #[test]
+    fn journalctl_args_carry_priority_and_since() {
+        let mut query = JournalQuery::new("demo.service", UnitScope::User);
+        query.max_priority = Priority::Error;
+        query.since = Some(1_700_000_100_500_000);
+        let args = query.journalctl_args(false);
+        assert!(args.contains(&"--priority=3".to_string()));
+        assert!(args.contains(&"--since=@1700000100".to_string()));
+        assert!(args.contains(&"--lines=1000".to_string()));
+        assert!(args.contains(&"--user".to_string()));
+        assert!(args.ends_with(&[
+            "_SYSTEMD_USER_UNIT=demo.service".to_string(),
+            "+".to_string(),
+            "USER_UNIT=demo.service".to_string(),
+        ]));
+
+        let follow = JournalQuery::new("demo.service", UnitScope::System).journalctl_args(true);
+        assert!(follow.contains(&"--priority=7".to_string()));
+        assert!(follow.contains(&"--lines=0".to_string()));
+        assert!(follow.contains(&"--follow".to_string()));
+        assert!(!follow.iter().any(|arg| arg.starts_with("--since")));
+    }
+}
@@ -0,0 +1,101 @@
+//! Simple service units, as the new service wizard collects them.
+
+use super::unitfile::{Section, UnitFile, quote_word};
+
+/// The restart policies offered for new services, most useful first.
+pub const RESTART_POLICIES: [&str; 7] = [
+    "on-failure",
+    "always",
+    "no",
+    "on-success",
+    "on-abnormal",
+    "on-abort",
+    "on-watchdog",
+];
+
+/// Settings for a new `.service` unit. Empty fields are left out of the
+/// generated file.
+#[derive(Debug, Clone, Default)]
+pub struct ServiceSpec {
+    /// The unit name, with or without the `.service` suffix.
+    pub name: String,
+    pub description: String,
+    pub exec_start: String,
+    pub user: String,
+    pub working_directory: String,
+    pub restart: String,
+    /// `KEY=value` assignments.
+    pub environment: Vec<String>,
+    /// Target that pulls the service in once enabled. Without one the unit
+    /// gets no `[Install]` section and can only be started by hand.
+    pub wanted_by: String,
+}
+
+impl ServiceSpec {
+    pub fn unit_name(&self) -> String {
+        let name = self.name.trim();
+        if name.ends_with(".service") {
+            name.to_string()
+        } else {
+            format!("{}.service", name)
+        }
+    }
+
+    pub fn to_unit_file(&self) -> UnitFile {
+        let mut file = UnitFile::new();
+        let unit = file.section_mut("Unit");
+        push_nonempty(unit, "Description", &self.description);
+
+        let service = file.section_mut("Service");
+        push_nonempty(service, "ExecStart", &self.exec_start);
+        push_nonempty(service, "User", &self.user);
+        push_nonempty(service, "WorkingDirectory", &self.working_directory);
+        if self.restart != "no" {
+            push_nonempty(service, "Restart", &self.restart);
+        }
+        for assignment in &self.environment {
+            service.push("Environment", &quote_word(assignment));
+        }
+
+        if !self.wanted_by.trim().is_empty() {
+            file.section_mut("Install")
+                .push("WantedBy", self.wanted_by.trim());
+        }
+        file
+    }
+
+    /// Problems the linter cannot see, such as malformed `Environment=`
+    /// assignments.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let name = self.name.trim().trim_end_matches(".service");
+        if name.is_empty() {
+            problems.push("The service needs a name".to_string());
+        } else if !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || ":-_.@".contains(c))
+        {
+            problems.push(format!(
+                "{} may only contain letters, digits and \":-_.@\"",
+                name
+            ));
+        }
+        for assignment in &self.environment {
+            let valid = assignment.split_once('=').is_some_and(|(key, _)| {
+                key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                    && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            });
+            if !valid {
+                problems.push(format!("{} is not a KEY=value assignment", assignment));
+            }
+        }
+        problems
+    }
+}
+
+fn push_nonempty(section: &mut Section, key: &str, value: &str) {
+    let value = value.trim();
+    if !value.is_empty() {
+        section.push(key, value);
+    }
+}
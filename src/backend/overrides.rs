@@ -0,0 +1,247 @@
+//! The files a unit is loaded from, `override.conf` drop-ins and new unit
+//! files.
+//!
+//! Reading works like `systemctl cat`: the fragment named by `FragmentPath`
+//! followed by every file in `DropInPaths`. System units and drop-ins live
+//! below `/etc`, so unless the app runs as root they are written through
+//! `pkexec`, which asks polkit before running anything.
+
+use super::unitfile::{FileKind, Severity, UnitFile};
+use super::{
+    PKEXEC_NOT_AUTHORIZED, Result, ServiceError, SystemdServiceManager, UnitScope, UnitType,
+    property,
+};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use users::get_effective_uid;
+
+const UNIT_INTERFACE: &str = "org.freedesktop.systemd1.Unit";
+const OVERRIDE_FILE: &str = "override.conf";
+
+/// A unit's fragment and drop-ins with their contents, in the order
+/// systemd applies them.
+#[derive(Debug, Clone)]
+pub struct UnitSource {
+    pub files: Vec<(PathBuf, String)>,
+    /// Where `override.conf` is, or would be created, for this unit.
+    pub override_path: PathBuf,
+}
+
+impl UnitSource {
+    /// All files concatenated with a `# path` header each, as `systemctl cat`
+    /// prints them.
+    pub fn merged(&self) -> String {
+        self.files
+            .iter()
+            .map(|(path, content)| format!("# {}\n{}", path.display(), content))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// The current content of `override.conf`, if the unit has one.
+    pub fn override_content(&self) -> Option<&str> {
+        self.files
+            .iter()
+            .find(|(path, _)| *path == self.override_path)
+            .map(|(_, content)| content.as_str())
+    }
+}
+
+impl SystemdServiceManager {
+    pub fn read_unit_source(&self, unit_name: &str, scope: UnitScope) -> Result<UnitSource> {
+        let override_path = override_path(unit_name, scope)?;
+        let conn = self.get_connection(scope)?;
+        let unit_path = self.load_unit_path(&conn, unit_name)?;
+        let unit = self.get_all_properties(&conn, &unit_path, UNIT_INTERFACE)?;
+
+        let fragment = property::<String>(&unit, "FragmentPath").filter(|p| !p.is_empty());
+        let drop_ins = property::<Vec<String>>(&unit, "DropInPaths").unwrap_or_default();
+        let files = fragment
+            .into_iter()
+            .chain(drop_ins)
+            .map(|path| {
+                let content = std::fs::read_to_string(&path)?;
+                Ok((PathBuf::from(path), content))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(UnitSource {
+            files,
+            override_path,
+        })
+    }
+
+    /// Writes the unit's `override.conf` and returns its path. The caller is
+    /// expected to reload the manager afterwards.
+    pub fn write_override(
+        &self,
+        unit_name: &str,
+        scope: UnitScope,
+        content: &str,
+    ) -> Result<PathBuf> {
+        let path = override_path(unit_name, scope)?;
+        write_scoped(&path, scope, content)?;
+        Ok(path)
+    }
+
+    /// Installs a new unit file into the configuration directory of `scope`
+    /// and reloads the manager so the unit can be started. Files the linter
+    /// finds errors in are refused, and an existing file is never replaced.
+    pub fn install_unit_file(
+        &self,
+        unit_name: &str,
+        scope: UnitScope,
+        file: &UnitFile,
+    ) -> Result<PathBuf> {
+        let path = unit_dir(unit_name, scope)?.join(unit_name);
+        if let Some(error) = file
+            .lint(unit_name, FileKind::Fragment)
+            .into_iter()
+            .find(|d| d.severity == Severity::Error)
+        {
+            return Err(ServiceError::InvalidUnitFile(error.to_string()));
+        }
+        // Checked up front to spare a polkit prompt; the file is still
+        // created exclusively, in case another one appeared since.
+        if path.exists() {
+            return Err(already_exists(&path));
+        }
+        create_scoped(&path, scope, &file.to_string())?;
+        self.daemon_reload(scope)?;
+        Ok(path)
+    }
+}
+
+/// `/etc/systemd/system/<unit>.d/override.conf`, or the same below
+/// `~/.config/systemd/user` for user units.
+fn override_path(unit_name: &str, scope: UnitScope) -> Result<PathBuf> {
+    Ok(unit_dir(unit_name, scope)?
+        .join(format!("{}.d", unit_name))
+        .join(OVERRIDE_FILE))
+}
+
+/// The directory administrators put units of `scope` in, after checking
+/// that `unit_name` can be used as a file name there.
+fn unit_dir(unit_name: &str, scope: UnitScope) -> Result<PathBuf> {
+    let valid_chars = unit_name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || ":-_.\\@".contains(c));
+    if !valid_chars || UnitType::from_name(unit_name).is_none() {
+        return Err(ServiceError::InvalidUnitName(unit_name.to_string()));
+    }
+    match scope {
+        UnitScope::System => Ok(PathBuf::from("/etc/systemd/system")),
+        UnitScope::User => Ok(user_config_dir()?.join("systemd/user")),
+    }
+}
+
+fn user_config_dir() -> Result<PathBuf> {
+    if let Some(dir) = std::env::var_os("XDG_CONFIG_HOME").filter(|d| !d.is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
+    std::env::var_os("HOME")
+        .map(|home| PathBuf::from(home).join(".config"))
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "HOME is not set").into())
+}
+
+fn write_scoped(path: &Path, scope: UnitScope, content: &str) -> Result<()> {
+    match scope {
+        UnitScope::User => write_file(path, content),
+        UnitScope::System if get_effective_uid() == 0 => write_file(path, content),
+        UnitScope::System => write_file_privileged(path, content),
+    }
+}
+
+/// Like [`write_scoped`], but fails instead of replacing an existing file.
+fn create_scoped(path: &Path, scope: UnitScope, content: &str) -> Result<()> {
+    match scope {
+        UnitScope::User => create_file(path, content),
+        UnitScope::System if get_effective_uid() == 0 => create_file(path, content),
+        UnitScope::System => create_file_privileged(path, content),
+    }
+}
+
+fn already_exists(path: &Path) -> ServiceError {
+    std::io::Error::new(
+        std::io::ErrorKind::AlreadyExists,
+        format!("{} already exists", path.display()),
+    )
+    .into()
+}
+
+fn write_file(path: &Path, content: &str) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, content).map_err(Into::into)
+}
+
+/// Creates the file with `O_EXCL`, so an existing one is never touched.
+fn create_file(path: &Path, content: &str) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::AlreadyExists => already_exists(path),
+            _ => e.into(),
+        })?;
+    file.write_all(content.as_bytes()).map_err(Into::into)
+}
+
+/// Creates the drop-in directory and writes the file as root, with
+/// `install -D` so pkexec gets a fixed argument list and never a shell. The
+/// content goes through stdin so it never shows up in a process listing.
+fn write_file_privileged(path: &Path, content: &str) -> Result<()> {
+    let mut command = Command::new("pkexec");
+    command
+        .args(["/usr/bin/install", "-D", "-m", "0644", "/dev/stdin"])
+        .arg(path);
+    run_privileged(command, path, content)
+}
+
+/// Creates the file as root without replacing an existing one: `dd` with
+/// `conv=excl` opens it with `O_EXCL`. Unlike [`write_file_privileged`]
+/// this creates no directories, as new units go straight into the unit
+/// directory.
+fn create_file_privileged(path: &Path, content: &str) -> Result<()> {
+    let mut of = std::ffi::OsString::from("of=");
+    of.push(path);
+    let mut command = Command::new("pkexec");
+    command
+        .arg("/usr/bin/dd")
+        .arg(of)
+        .args(["conv=excl", "status=none"]);
+    run_privileged(command, path, content)
+}
+
+/// Runs a pkexec `command` that reads the content of `path` from stdin.
+fn run_privileged(mut command: Command, path: &Path, content: &str) -> Result<()> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(content.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    match output.status.code() {
+        Some(0) => Ok(()),
+        Some(code) if PKEXEC_NOT_AUTHORIZED.contains(&code) => {
+            Err(ServiceError::AuthorizationFailed(format!(
+                "Not authorized to write {}",
+                path.display()
+            )))
+        }
+        _ => Err(std::io::Error::other(format!(
+            "Writing {} failed: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+        .into()),
+    }
+}
@@ -0,0 +1,300 @@
+//! The processes in a unit's control group.
+//!
+//! The manager's `GetUnitProcesses` only knows the cgroup, PID and command
+//! line of each process. Parent, owner, CPU time and memory come from
+//! `/proc`, which may hide other users' processes; those fields are then
+//! left empty.
+
+use super::{
+    KillWhom, PKEXEC_NOT_AUTHORIZED, Result, ServiceError, Signal, SystemdServiceManager,
+    UnitScope, property,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::process::{Command, Stdio};
+use users::{get_effective_uid, get_user_by_uid};
+
+/// Clock ticks per second in `/proc/<pid>/stat`. The kernel reports these
+/// in USER_HZ, which is 100 on every architecture Linux supports today.
+const USER_HZ: u64 = 100;
+
+#[derive(Debug, Clone)]
+pub struct UnitProcess {
+    pub pid: u32,
+    /// 0 if `/proc` could not be read.
+    pub parent: u32,
+    /// The control group path, relative to the cgroup root.
+    pub cgroup: String,
+    pub command: String,
+    pub uid: Option<u32>,
+    /// User name, or the UID if it has no passwd entry.
+    pub user: Option<String>,
+    /// User plus system time in ns.
+    pub cpu_time: Option<u64>,
+    /// Resident set size in bytes.
+    pub rss: Option<u64>,
+    /// Levels below the closest ancestor that is not in the unit.
+    pub depth: usize,
+    /// Start time in clock ticks after boot, to tell a PID from its reuse.
+    start_time: Option<u64>,
+}
+
+impl UnitProcess {
+    fn new(cgroup: String, pid: u32, dbus_command: String) -> Self {
+        let stat = read_stat(pid);
+        let status = read_status(pid);
+        let command = std::fs::read(format!("/proc/{}/cmdline", pid))
+            .ok()
+            .map(|cmdline| {
+                cmdline
+                    .split(|b| *b == 0)
+                    .filter(|arg| !arg.is_empty())
+                    .map(String::from_utf8_lossy)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .filter(|command| !command.is_empty())
+            .unwrap_or(dbus_command);
+        Self {
+            pid,
+            parent: stat.as_ref().map_or(0, |stat| stat.parent),
+            cgroup,
+            command,
+            uid: status.uid,
+            user: None,
+            cpu_time: stat
+                .as_ref()
+                .map(|stat| stat.cpu_ticks * 1_000_000_000 / USER_HZ),
+            rss: status.rss,
+            depth: 0,
+            start_time: stat.map(|stat| stat.start_time),
+        }
+    }
+}
+
+struct Stat {
+    parent: u32,
+    cpu_ticks: u64,
+    start_time: u64,
+}
+
+/// Reads `/proc/<pid>/stat`. The command name in parentheses may contain
+/// spaces and parentheses itself, so fields are counted from the last `)`.
+fn read_stat(pid: u32) -> Option<Stat> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    let (_, rest) = stat.rsplit_once(')')?;
+    // The first field after the name is field 3, `state`.
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    let field = |n: usize| fields.get(n - 3)?.parse::<u64>().ok();
+    Some(Stat {
+        parent: field(4)? as u32,
+        cpu_ticks: field(14)? + field(15)?,
+        start_time: field(22)?,
+    })
+}
+
+#[derive(Default)]
+struct Status {
+    uid: Option<u32>,
+    rss: Option<u64>,
+}
+
+fn read_status(pid: u32) -> Status {
+    let mut status = Status::default();
+    let Ok(content) = std::fs::read_to_string(format!("/proc/{}/status", pid)) else {
+        return status;
+    };
+    for line in content.lines() {
+        if let Some(uids) = line.strip_prefix("Uid:") {
+            // Real, effective, saved and filesystem UID; ps shows the
+            // effective one.
+            status.uid = uids.split_whitespace().nth(1).and_then(|u| u.parse().ok());
+        } else if let Some(rss) = line.strip_prefix("VmRSS:") {
+            status.rss = rss
+                .trim()
+                .strip_suffix("kB")
+                .and_then(|kib| kib.trim().parse::<u64>().ok())
+                .map(|kib| kib * 1024);
+        }
+    }
+    status
+}
+
+/// Orders processes depth first under their parents, with children by PID.
+/// A process whose parent is outside the unit starts a new tree.
+fn into_tree(processes: Vec<UnitProcess>) -> Vec<UnitProcess> {
+    let mut by_pid: BTreeMap<u32, UnitProcess> =
+        processes.into_iter().map(|p| (p.pid, p)).collect();
+    let mut children: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+    let mut roots = Vec::new();
+    for process in by_pid.values() {
+        if process.parent != process.pid && by_pid.contains_key(&process.parent) {
+            children
+                .entry(process.parent)
+                .or_default()
+                .push(process.pid);
+        } else {
+            roots.push(process.pid);
+        }
+    }
+
+    let mut tree = Vec::with_capacity(by_pid.len());
+    let mut stack: Vec<(u32, usize)> = roots.into_iter().rev().map(|pid| (pid, 0)).collect();
+    while let Some((pid, depth)) = stack.pop() {
+        let Some(mut process) = by_pid.remove(&pid) else {
+            continue;
+        };
+        process.depth = depth;
+        tree.push(process);
+        if let Some(pids) = children.get(&pid) {
+            stack.extend(pids.iter().rev().map(|child| (*child, depth + 1)));
+        }
+    }
+    // Parent loops cannot happen, but nothing should get lost if they do.
+    tree.extend(by_pid.into_values());
+    tree
+}
+
+impl SystemdServiceManager {
+    /// Lists the processes of a loaded unit as a tree, see [`UnitProcess::depth`].
+    pub fn unit_processes(&self, unit_name: &str, scope: UnitScope) -> Result<Vec<UnitProcess>> {
+        let conn = self.get_connection(scope)?;
+        let listed: Vec<(String, u32, String)> = self
+            .get_manager_proxy(&conn)?
+            .call("GetUnitProcesses", &(unit_name,))?;
+
+        let mut users: HashMap<u32, String> = HashMap::new();
+        let processes = listed
+            .into_iter()
+            .map(|(cgroup, pid, command)| {
+                let mut process = UnitProcess::new(cgroup, pid, command);
+                process.user = process.uid.map(|uid| {
+                    users
+                        .entry(uid)
+                        .or_insert_with(|| {
+                            get_user_by_uid(uid)
+                                .map(|user| user.name().to_string_lossy().into_owned())
+                                .unwrap_or_else(|| uid.to_string())
+                        })
+                        .clone()
+                });
+                process
+            })
+            .collect();
+        Ok(into_tree(processes))
+    }
+
+    /// Sends `signal` to a single process of `unit_name`. The unit's main
+    /// and control process go through `KillUnit`, as the manager knows
+    /// exactly which process that is. Other processes of other users are
+    /// signalled through pkexec; if the owner is unknown, pkexec is only
+    /// asked once the kernel refused the signal.
+    ///
+    /// Fails if the PID now belongs to a different process than the one
+    /// listed, as far as `/proc` tells. That is checked right before the
+    /// signal is sent, but a PID can still be reused in between: for
+    /// kill(2) that is a few instructions, for pkexec as long as the
+    /// authentication prompt stays open.
+    pub fn signal_process(
+        &self,
+        unit_name: &str,
+        scope: UnitScope,
+        process: &UnitProcess,
+        signal: Signal,
+    ) -> Result<()> {
+        ensure_unchanged(process)?;
+        if let Some(whom) = self.tracked_as(unit_name, scope, process.pid)? {
+            return self.kill_unit(unit_name, whom, signal, scope);
+        }
+
+        let euid = get_effective_uid();
+        let foreign = euid != 0 && process.uid.is_some_and(|uid| uid != euid);
+        if !foreign {
+            ensure_unchanged(process)?;
+            let Err(e) = kill(process.pid, signal) else {
+                return Ok(());
+            };
+            let refused = e.raw_os_error() == Some(libc::EPERM);
+            if euid == 0 || process.uid.is_some() || !refused {
+                return Err(std::io::Error::new(
+                    e.kind(),
+                    format!("Sending {} to {} failed: {}", signal.name(), process.pid, e),
+                )
+                .into());
+            }
+        }
+        ensure_unchanged(process)?;
+        kill_privileged(process.pid, signal)
+    }
+
+    /// Whether the manager tracks `pid` as the unit's main or control
+    /// process.
+    fn tracked_as(&self, unit_name: &str, scope: UnitScope, pid: u32) -> Result<Option<KillWhom>> {
+        let interface = match unit_name.rsplit_once('.').map(|(_, suffix)| suffix) {
+            Some("service") => "org.freedesktop.systemd1.Service",
+            Some("socket") => "org.freedesktop.systemd1.Socket",
+            Some("mount") => "org.freedesktop.systemd1.Mount",
+            Some("swap") => "org.freedesktop.systemd1.Swap",
+            _ => return Ok(None),
+        };
+        let conn = self.get_connection(scope)?;
+        let Some(unit) = self.find_listed_unit(&conn, unit_name)? else {
+            return Ok(None);
+        };
+        let properties = self.get_all_properties(&conn, &unit.path, interface)?;
+        Ok(if property::<u32>(&properties, "MainPID") == Some(pid) {
+            Some(KillWhom::Main)
+        } else if property::<u32>(&properties, "ControlPID") == Some(pid) {
+            Some(KillWhom::Control)
+        } else {
+            None
+        })
+    }
+}
+
+/// Fails if the PID no longer names the listed process.
+fn ensure_unchanged(process: &UnitProcess) -> Result<()> {
+    let current = read_stat(process.pid).map(|stat| stat.start_time);
+    if process.start_time.is_some() && current != process.start_time {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("Process {} has exited", process.pid),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+fn kill(pid: u32, signal: Signal) -> std::io::Result<()> {
+    let pid = libc::pid_t::try_from(pid).map_err(std::io::Error::other)?;
+    // SAFETY: kill(2) takes plain integers and touches no memory of ours.
+    match unsafe { libc::kill(pid, signal.number()) } {
+        0 => Ok(()),
+        _ => Err(std::io::Error::last_os_error()),
+    }
+}
+
+/// Signals a process of another user with `kill` run by pkexec, with a
+/// fixed argument list.
+fn kill_privileged(pid: u32, signal: Signal) -> Result<()> {
+    let name = signal.name().trim_start_matches("SIG");
+    let output = Command::new("pkexec")
+        .args(["/usr/bin/kill", "-s", name])
+        .arg(pid.to_string())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()?;
+    match output.status.code() {
+        Some(0) => Ok(()),
+        Some(code) if PKEXEC_NOT_AUTHORIZED.contains(&code) => Err(
+            ServiceError::AuthorizationFailed(format!("Not authorized to signal process {}", pid)),
+        ),
+        _ => Err(std::io::Error::other(format!(
+            "Sending {} to {} failed: {}",
+            signal.name(),
+            pid,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+        .into()),
+    }
+}
@@ -0,0 +1,149 @@
+//! Template units such as `getty@.service` and their instances.
+//!
+//! An instance name is the template name with an escaped instance string
+//! between the `@` and the suffix, e.g. `getty@tty1.service`. The escaping
+//! follows `systemd-escape`.
+
+use super::{Result, ServiceError};
+
+/// Longest unit name systemd accepts, including the suffix.
+const UNIT_NAME_MAX: usize = 255;
+
+/// Whether `name` is a template, i.e. has an `@` directly followed by the
+/// suffix.
+pub fn is_template(name: &str) -> bool {
+    name.split_once('@')
+        .is_some_and(|(prefix, rest)| !prefix.is_empty() && rest.starts_with('.'))
+}
+
+/// The template an instance was created from: `getty@tty1.service` gives
+/// `getty@.service`. `None` for templates and plain units.
+pub fn template_of(name: &str) -> Option<String> {
+    let (prefix, rest) = name.split_once('@')?;
+    let (instance, suffix) = rest.rsplit_once('.')?;
+    (!prefix.is_empty() && !instance.is_empty()).then(|| format!("{}@.{}", prefix, suffix))
+}
+
+/// Escapes a string for use as an instance, as `systemd-escape` does: `/`
+/// becomes `-`, and everything outside `[A-Za-z0-9:_.]` as well as a leading
+/// `.` is written as `\xNN`.
+pub fn escape_instance(instance: &str) -> String {
+    let mut escaped = String::with_capacity(instance.len());
+    for (i, byte) in instance.bytes().enumerate() {
+        match byte {
+            b'/' => escaped.push('-'),
+            b'.' if i == 0 => escaped.push_str("\\x2e"),
+            b if b.is_ascii_alphanumeric() || matches!(b, b':' | b'_' | b'.') => {
+                escaped.push(b as char)
+            }
+            b => escaped.push_str(&format!("\\x{:02x}", b)),
+        }
+    }
+    escaped
+}
+
+/// Builds the instance name of `template` for the unescaped `instance`.
+pub fn instance_name(template: &str, instance: &str) -> Result<String> {
+    if !is_template(template) {
+        return Err(ServiceError::InvalidUnitName(format!(
+            "{} is not a template unit",
+            template
+        )));
+    }
+    if instance.is_empty() {
+        return Err(ServiceError::InvalidUnitName(
+            "The instance name must not be empty".to_string(),
+        ));
+    }
+    let (prefix, suffix) = template.split_once('@').unwrap_or_default();
+    let name = format!("{}@{}{}", prefix, escape_instance(instance), suffix);
+    if name.len() > UNIT_NAME_MAX {
+        return Err(ServiceError::InvalidUnitName(format!(
+            "{} is longer than {} characters",
+            name, UNIT_NAME_MAX
+        )));
+    }
+    Ok(name)
+}
+
+/// Reverses [`escape_instance`]: `-` becomes `/` and `\xNN` the byte it
+/// stands for, as `systemd-escape --unescape` does.
+pub fn unescape_instance(escaped: &str) -> String {
+    let mut bytes = Vec::with_capacity(escaped.len());
+    let mut rest = escaped.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+        match byte {
+            b'-' => bytes.push(b'/'),
+            b'\\' if tail.first() == Some(&b'x') => {
+                let decoded = tail
+                    .get(1..3)
+                    .and_then(|hex| std::str::from_utf8(hex).ok())
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                match decoded {
+                    Some(decoded) => {
+                        bytes.push(decoded);
+                        rest = &tail[3..];
+                    }
+                    None => bytes.push(byte),
+                }
+            }
+            byte => bytes.push(byte),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pairs as `systemd-escape` prints them.
+    const ESCAPED: &[(&str, &str)] = &[
+        ("tty1", "tty1"),
+        ("/dev/sda1", "-dev-sda1"),
+        ("my-app", "my\\x2dapp"),
+        (".hidden", "\\x2ehidden"),
+        ("a.b", "a.b"),
+        ("host:8080", "host:8080"),
+        ("with space", "with\\x20space"),
+        ("grüße", "gr\\xc3\\xbc\\xc3\\x9fe"),
+    ];
+
+    #[test]
+    fn escape_instance_matches_systemd_escape() {
+        for (instance, escaped) in ESCAPED {
+            assert_eq!(escape_instance(instance), *escaped, "escaping {}", instance);
+        }
+    }
+
+    #[test]
+    fn unescape_instance_reverses_escape() {
+        for (instance, escaped) in ESCAPED {
+            assert_eq!(
+                unescape_instance(escaped),
+                *instance,
+                "unescaping {}",
+                escaped
+            );
+            assert_eq!(unescape_instance(&escape_instance(instance)), *instance);
+        }
+    }
+
+    #[test]
+    fn unescape_instance_keeps_broken_escapes() {
+        assert_eq!(unescape_instance("a\\xzz"), "a\\xzz");
+        assert_eq!(unescape_instance("a\\x4"), "a\\x4");
+    }
+
+    #[test]
+    fn instance_name_checks_template_and_instance() {
+        assert_eq!(
+            instance_name("getty@.service", "/dev/tty1").unwrap(),
+            "getty@-dev-tty1.service"
+        );
+        assert!(instance_name("getty.service", "tty1").is_err());
+        assert!(instance_name("getty@.service", "").is_err());
+        assert!(instance_name("getty@.service", &"x".repeat(UNIT_NAME_MAX)).is_err());
+    }
+}
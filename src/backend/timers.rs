@@ -0,0 +1,73 @@
+//! Schedules of the loaded `.timer` units.
+
+use super::{Properties, Result, SystemdServiceManager, UnitScope, UnitType, property};
+use rayon::prelude::*;
+
+/// A timer and its schedule. Timestamps are microseconds with 0 meaning
+/// "never"; the monotonic one counts from boot.
+#[derive(Debug, Clone)]
+pub struct TimerInfo {
+    pub name: String,
+    pub scope: UnitScope,
+    /// The unit the timer activates, usually the `.service` of the same name.
+    pub unit: String,
+    pub active_state: String,
+    pub next_elapse_realtime: u64,
+    pub next_elapse_monotonic: u64,
+    pub last_trigger: u64,
+    /// Result of the last run of the timer itself, e.g. `success`.
+    pub result: String,
+}
+
+impl TimerInfo {
+    fn from_properties(
+        name: String,
+        active_state: String,
+        scope: UnitScope,
+        timer: &Properties,
+    ) -> Self {
+        let timestamp = |key| property::<u64>(timer, key).unwrap_or(0);
+        Self {
+            name,
+            scope,
+            unit: property(timer, "Unit").unwrap_or_default(),
+            active_state,
+            next_elapse_realtime: timestamp("NextElapseUSecRealtime"),
+            next_elapse_monotonic: timestamp("NextElapseUSecMonotonic"),
+            last_trigger: timestamp("LastTriggerUSec"),
+            result: property(timer, "Result").unwrap_or_default(),
+        }
+    }
+}
+
+impl SystemdServiceManager {
+    /// Lists the timers of both managers, skipping a bus that is not
+    /// reachable.
+    pub fn list_timers(&self) -> Result<Vec<TimerInfo>> {
+        let (system, user) = rayon::join(
+            || self.list_scope_timers(UnitScope::System),
+            || self.list_scope_timers(UnitScope::User),
+        );
+        Ok(system.into_iter().chain(user).flatten().collect())
+    }
+
+    fn list_scope_timers(&self, scope: UnitScope) -> Result<Vec<TimerInfo>> {
+        let conn = self.get_connection(scope)?;
+        let timers = self
+            .call_list_units(&conn)?
+            .into_par_iter()
+            .filter(|unit| UnitType::from_name(&unit.name) == Some(UnitType::Timer))
+            .filter_map(|unit| {
+                let interface = UnitType::Timer.info_interface()?;
+                let properties = self.get_all_properties(&conn, &unit.path, interface).ok()?;
+                Some(TimerInfo::from_properties(
+                    unit.name,
+                    unit.active_state,
+                    scope,
+                    &properties,
+                ))
+            })
+            .collect();
+        Ok(timers)
+    }
+}
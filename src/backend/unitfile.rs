@@ -0,0 +1,1502 @@
+//! Parser and linter for the INI dialect of systemd unit files.
+//!
+//! Parsing follows systemd's loader: `#` and `;` start comment lines, a
+//! trailing backslash continues a line, sections may repeat and keys are
+//! kept in order, so list settings see every assignment. The lint checks
+//! what `systemd-analyze verify` would complain about. Nothing here talks to
+//! systemd; a file can be checked before it is installed.
+
+use super::UnitType;
+use super::templates::unescape_instance;
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+/// A problem found in a unit file. `line` is 1-based and `None` for
+/// problems with the file as a whole.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub line: Option<usize>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn new(line: Option<usize>, severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            severity,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "Line {}: {}", line, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// One `Key=value` assignment, with continuations joined.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub key: String,
+    pub value: String,
+    /// Line the assignment starts on, 0 for entries built in code.
+    pub line: usize,
+    /// Comment lines directly above the entry, including their `#`.
+    pub comments: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    pub name: String,
+    pub line: usize,
+    pub comments: Vec<String>,
+    pub entries: Vec<Entry>,
+}
+
+impl Section {
+    fn new(name: &str, line: usize) -> Self {
+        Self {
+            name: name.to_string(),
+            line,
+            comments: Vec::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Appends an assignment, adding to list settings such as `After=`.
+    pub fn push(&mut self, key: &str, value: &str) {
+        self.entries.push(Entry {
+            key: key.to_string(),
+            value: value.to_string(),
+            line: 0,
+            comments: Vec::new(),
+        });
+    }
+}
+
+/// A parsed unit file or drop-in. Printing it writes it back in the same
+/// dialect.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UnitFile {
+    pub sections: Vec<Section>,
+    /// Comments after the last entry.
+    pub trailing_comments: Vec<String>,
+    syntax_errors: Vec<Diagnostic>,
+}
+
+/// Whether a file defines a unit or only amends one. Drop-ins need not be
+/// complete, so the checks for required settings are skipped for them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    Fragment,
+    DropIn,
+}
+
+impl UnitFile {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses `source`. Malformed lines are skipped, as systemd does, and
+    /// reported by [`UnitFile::lint`].
+    pub fn parse(source: &str) -> Self {
+        let mut file = Self::new();
+        let mut comments = Vec::new();
+        let mut logical: Option<(usize, String)> = None;
+
+        for (index, raw) in source.lines().enumerate() {
+            let number = index + 1;
+            let trimmed = raw.trim();
+            if logical.is_some() && is_comment(trimmed) {
+                continue;
+            }
+            let (start, mut text) = match logical.take() {
+                Some((start, text)) => (start, text + trimmed),
+                None => (number, trimmed.to_string()),
+            };
+            if let Some(continued) = text.strip_suffix('\\') {
+                text = format!("{} ", continued.trim_end());
+                logical = Some((start, text));
+                continue;
+            }
+            file.parse_line(start, &text, &mut comments);
+        }
+        if let Some((start, text)) = logical {
+            file.parse_line(start, text.trim_end(), &mut comments);
+        }
+        file.trailing_comments = comments;
+        file
+    }
+
+    fn parse_line(&mut self, line: usize, text: &str, comments: &mut Vec<String>) {
+        if text.is_empty() {
+            return;
+        }
+        if is_comment(text) {
+            comments.push(text.to_string());
+            return;
+        }
+        if let Some(header) = text.strip_prefix('[') {
+            match header.strip_suffix(']').filter(|name| !name.is_empty()) {
+                Some(name) => {
+                    let mut section = Section::new(name, line);
+                    section.comments = std::mem::take(comments);
+                    self.sections.push(section);
+                }
+                None => self.syntax_error(line, format!("Invalid section header {}", text)),
+            }
+            return;
+        }
+        let Some((key, value)) = text.split_once('=') else {
+            self.syntax_error(line, format!("Missing '=' in \"{}\"", text));
+            return;
+        };
+        let key = key.trim_end();
+        if key.is_empty() {
+            self.syntax_error(line, "Assignment without a key");
+            return;
+        }
+        let Some(section) = self.sections.last_mut() else {
+            self.syntax_error(line, format!("{} is set outside of any section", key));
+            return;
+        };
+        section.entries.push(Entry {
+            key: key.to_string(),
+            value: value.trim_start().to_string(),
+            line,
+            comments: std::mem::take(comments),
+        });
+    }
+
+    fn syntax_error(&mut self, line: usize, message: impl Into<String>) {
+        self.syntax_errors
+            .push(Diagnostic::new(Some(line), Severity::Error, message));
+    }
+
+    pub fn has_section(&self, name: &str) -> bool {
+        self.sections.iter().any(|section| section.name == name)
+    }
+
+    /// The last section called `name`, appended if there is none yet.
+    pub fn section_mut(&mut self, name: &str) -> &mut Section {
+        match self
+            .sections
+            .iter()
+            .rposition(|section| section.name == name)
+        {
+            Some(index) => &mut self.sections[index],
+            None => {
+                self.sections.push(Section::new(name, 0));
+                self.sections.last_mut().expect("section was just added")
+            }
+        }
+    }
+
+    /// Every assignment of `key` in `section`, in file order.
+    pub fn entries<'a, 'k>(
+        &'a self,
+        section: &'k str,
+        key: &'k str,
+    ) -> impl Iterator<Item = &'a Entry> + 'k
+    where
+        'a: 'k,
+    {
+        self.sections
+            .iter()
+            .filter(move |s| s.name == section)
+            .flat_map(|s| &s.entries)
+            .filter(move |entry| entry.key == key)
+    }
+
+    /// The effective value of a single-valued setting: the last assignment.
+    pub fn value(&self, section: &str, key: &str) -> Option<&str> {
+        self.entries(section, key)
+            .last()
+            .map(|entry| entry.value.as_str())
+    }
+
+    /// The effective values of a list setting. An empty assignment clears
+    /// what was set before it.
+    pub fn values(&self, section: &str, key: &str) -> Vec<&str> {
+        let mut values = Vec::new();
+        for entry in self.entries(section, key) {
+            if entry.value.is_empty() {
+                values.clear();
+            } else {
+                values.push(entry.value.as_str());
+            }
+        }
+        values
+    }
+
+    /// Checks the file as the unit `unit_name` and returns what is wrong
+    /// with it, sorted by line.
+    pub fn lint(&self, unit_name: &str, kind: FileKind) -> Vec<Diagnostic> {
+        let unit_type = UnitType::from_name(unit_name);
+        let specifiers = Specifiers::for_unit(unit_name);
+        let mut diagnostics = self.syntax_errors.clone();
+
+        for section in &self.sections {
+            if section.name.starts_with("X-") {
+                continue;
+            }
+            let Some(known) = known_keys(&section.name) else {
+                diagnostics.push(Diagnostic::new(
+                    Some(section.line),
+                    Severity::Warning,
+                    format!("Unknown section [{}], ignoring", section.name),
+                ));
+                continue;
+            };
+            if !section_applies(&section.name, unit_type) {
+                diagnostics.push(Diagnostic::new(
+                    Some(section.line),
+                    Severity::Warning,
+                    format!("[{}] has no effect in {}", section.name, unit_name),
+                ));
+            }
+            for entry in &section.entries {
+                lint_entry(entry, &section.name, known, &specifiers, &mut diagnostics);
+            }
+        }
+
+        if kind == FileKind::Fragment {
+            self.lint_required(unit_type, &mut diagnostics);
+        }
+        diagnostics.sort_by_key(|d| (d.line, d.severity));
+        diagnostics
+    }
+
+    fn lint_required(&self, unit_type: Option<UnitType>, diagnostics: &mut Vec<Diagnostic>) {
+        if unit_type == Some(UnitType::Service) {
+            let line = self
+                .sections
+                .iter()
+                .find(|section| section.name == "Service")
+                .map(|section| section.line);
+            let exec_start = self.values("Service", "ExecStart");
+            if exec_start.is_empty() && self.values("Service", "ExecStop").is_empty() {
+                diagnostics.push(Diagnostic::new(
+                    line,
+                    Severity::Error,
+                    "Service has no ExecStart= or ExecStop= setting",
+                ));
+            }
+            let oneshot = self.value("Service", "Type") == Some("oneshot");
+            let commands: usize = exec_start
+                .iter()
+                .filter_map(|value| parse_exec(value).ok())
+                .map(|commands| commands.len())
+                .sum();
+            if !oneshot && commands > 1 {
+                diagnostics.push(Diagnostic::new(
+                    line,
+                    Severity::Error,
+                    "Service has more than one ExecStart= command, which only Type=oneshot allows",
+                ));
+            }
+        }
+        let installable = !matches!(
+            unit_type,
+            Some(UnitType::Device | UnitType::Scope | UnitType::Slice)
+        );
+        if installable && !self.has_section("Install") {
+            diagnostics.push(Diagnostic::new(
+                None,
+                Severity::Info,
+                "No [Install] section, so the unit cannot be enabled",
+            ));
+        }
+    }
+}
+
+impl fmt::Display for UnitFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, section) in self.sections.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            for comment in &section.comments {
+                writeln!(f, "{}", comment)?;
+            }
+            writeln!(f, "[{}]", section.name)?;
+            for entry in &section.entries {
+                for comment in &entry.comments {
+                    writeln!(f, "{}", comment)?;
+                }
+                writeln!(f, "{}={}", entry.key, entry.value)?;
+            }
+        }
+        for comment in &self.trailing_comments {
+            writeln!(f, "{}", comment)?;
+        }
+        Ok(())
+    }
+}
+
+/// Quotes `word` so that systemd reads it back as a single word, e.g. an
+/// `Environment=` assignment containing spaces.
+pub fn quote_word(word: &str) -> String {
+    if !word.is_empty() && !word.contains(|c: char| c.is_whitespace() || "\"'\\".contains(c)) {
+        return word.to_string();
+    }
+    format!("\"{}\"", word.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn is_comment(line: &str) -> bool {
+    line.starts_with('#') || line.starts_with(';')
+}
+
+fn lint_entry(
+    entry: &Entry,
+    section: &str,
+    known: &[&[&str]],
+    specifiers: &Specifiers,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let line = Some(entry.line);
+    let key = entry.key.as_str();
+    if key.starts_with("X-") {
+        return;
+    }
+    let condition = key
+        .strip_prefix("Condition")
+        .or_else(|| key.strip_prefix("Assert"));
+    let known_key = known.iter().any(|keys| keys.contains(&key))
+        || (section == "Unit" && condition.is_some_and(|c| CONDITIONS.contains(&c)));
+    if !known_key {
+        diagnostics.push(Diagnostic::new(
+            line,
+            Severity::Warning,
+            format!("Unknown key {} in [{}], ignoring", key, section),
+        ));
+        return;
+    }
+
+    if let Err(e) = specifiers.expand(&entry.value) {
+        diagnostics.push(Diagnostic::new(
+            line,
+            Severity::Error,
+            format!("{} in {}={}", e, key, entry.value),
+        ));
+        return;
+    }
+
+    let has_specifier = entry.value.contains('%');
+    if key.ends_with("Sec")
+        && !TIME_SPAN_EXCEPTIONS.contains(&key)
+        && !entry.value.is_empty()
+        && !has_specifier
+        && parse_time_span(&entry.value).is_none()
+    {
+        diagnostics.push(Diagnostic::new(
+            line,
+            Severity::Error,
+            format!("Invalid time span {}={}", key, entry.value),
+        ));
+    }
+
+    if key.starts_with("Exec") && EXEC_KEYS.contains(&key) {
+        match parse_exec(&entry.value) {
+            Ok(commands) => {
+                for command in commands {
+                    if command.path.contains('/')
+                        && !command.path.starts_with('/')
+                        && !command.path.starts_with('%')
+                    {
+                        diagnostics.push(Diagnostic::new(
+                            line,
+                            Severity::Error,
+                            format!(
+                                "{}= path {} is relative, use an absolute path",
+                                key, command.path
+                            ),
+                        ));
+                    }
+                }
+            }
+            Err(e) => diagnostics.push(Diagnostic::new(
+                line,
+                Severity::Error,
+                format!("Invalid {}=: {}", key, e),
+            )),
+        }
+    }
+}
+
+/// One command of an `Exec*=` setting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecCommand {
+    /// Special prefix characters such as `-` (failure is ignored) or `+`
+    /// (runs with full privileges).
+    pub prefixes: String,
+    pub path: String,
+    /// The arguments, starting with `argv[0]`. That is the path unless the
+    /// `@` prefix gives it separately.
+    pub argv: Vec<String>,
+}
+
+const EXEC_PREFIXES: &[char] = &['-', '@', ':', '+', '!'];
+
+/// Splits an `Exec*=` value into commands the way systemd does: words are
+/// separated by whitespace, may be quoted with `"` or `'` and use C escapes,
+/// and a lone `;` separates commands.
+pub fn parse_exec(value: &str) -> std::result::Result<Vec<ExecCommand>, String> {
+    let mut commands = Vec::new();
+    let mut words = Vec::new();
+    for (word, quoted) in split_words(value)? {
+        if word == ";" && !quoted {
+            commands.push(exec_command(std::mem::take(&mut words))?);
+        } else {
+            words.push(word);
+        }
+    }
+    if !words.is_empty() {
+        commands.push(exec_command(words)?);
+    }
+    Ok(commands)
+}
+
+fn exec_command(words: Vec<String>) -> std::result::Result<ExecCommand, String> {
+    let mut words = words.into_iter();
+    let first = words.next().ok_or("Empty command")?;
+    let path = first.trim_start_matches(EXEC_PREFIXES);
+    let prefixes = first[..first.len() - path.len()].to_string();
+    if path.is_empty() {
+        return Err("Missing executable path".to_string());
+    }
+    let path = path.to_string();
+    let mut argv: Vec<String> = words.collect();
+    if prefixes.contains('@') {
+        if argv.is_empty() {
+            return Err(format!("{} needs an argv[0] after the path", path));
+        }
+    } else {
+        argv.insert(0, path.clone());
+    }
+    Ok(ExecCommand {
+        prefixes,
+        path,
+        argv,
+    })
+}
+
+/// Words of `value` with whether any part of them was quoted or escaped, so
+/// a literal `";"` or `\;` is not taken for a command separator.
+fn split_words(value: &str) -> std::result::Result<Vec<(String, bool)>, String> {
+    let mut words = Vec::new();
+    let mut chars = value.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            return Ok(words);
+        }
+        let mut word = String::new();
+        let mut quoted = false;
+        let mut quote = None;
+        while let Some(c) = chars.next() {
+            match (c, quote) {
+                ('\\', _) => {
+                    word.push(unescape(&mut chars)?);
+                    quoted = true;
+                }
+                ('"' | '\'', None) => {
+                    quote = Some(c);
+                    quoted = true;
+                }
+                (c, Some(q)) if c == q => quote = None,
+                (c, None) if c.is_whitespace() => break,
+                (c, _) => word.push(c),
+            }
+        }
+        if quote.is_some() {
+            return Err("Unterminated quote".to_string());
+        }
+        words.push((word, quoted));
+    }
+}
+
+fn unescape(chars: &mut std::iter::Peekable<std::str::Chars>) -> std::result::Result<char, String> {
+    let c = chars.next().ok_or("Trailing backslash")?;
+    Ok(match c {
+        'a' => '\x07',
+        'b' => '\x08',
+        'f' => '\x0c',
+        'n' => '\n',
+        'r' => '\r',
+        't' => '\t',
+        'v' => '\x0b',
+        's' => ' ',
+        'x' => {
+            let hex: String = chars.take(2).collect();
+            u8::from_str_radix(&hex, 16)
+                .map(char::from)
+                .map_err(|_| format!("Invalid escape \\x{}", hex))?
+        }
+        '\\' | '"' | '\'' | ';' | ' ' => c,
+        c => return Err(format!("Invalid escape \\{}", c)),
+    })
+}
+
+/// Multipliers of the time span units systemd understands, in
+/// microseconds.
+const TIME_UNITS: &[(&[&str], u64)] = &[
+    (&["usec", "us", "µs", "μs"], 1),
+    (&["msec", "ms"], 1_000),
+    (&["seconds", "second", "sec", "s"], 1_000_000),
+    (&["minutes", "minute", "min", "m"], 60_000_000),
+    (&["hours", "hour", "hr", "h"], 3_600_000_000),
+    (&["days", "day", "d"], 86_400_000_000),
+    (&["weeks", "week", "w"], 604_800_000_000),
+    (&["months", "month", "M"], 2_629_800_000_000),
+    (&["years", "year", "y"], 31_557_600_000_000),
+];
+
+/// Parses a time span such as `90`, `1min 30s` or `infinity` into
+/// microseconds, as systemd does for `*Sec=` settings. A bare number is
+/// seconds.
+pub fn parse_time_span(value: &str) -> Option<u64> {
+    let value = value.trim();
+    if value == "infinity" {
+        return Some(u64::MAX);
+    }
+    let mut rest = value;
+    let mut total: u64 = 0;
+    let mut components = 0;
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let (number, tail) = rest.split_at(number_end);
+        let tail = tail.trim_start();
+        let unit_end = tail
+            .find(|c: char| !c.is_alphabetic())
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_end);
+        let multiplier = match unit {
+            "" => 1_000_000,
+            unit => {
+                TIME_UNITS
+                    .iter()
+                    .find(|(names, _)| names.contains(&unit))?
+                    .1
+            }
+        };
+        let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+        if whole.is_empty() && fraction.is_empty() {
+            return None;
+        }
+        let whole: u64 = if whole.is_empty() {
+            0
+        } else {
+            whole.parse().ok()?
+        };
+        let fraction = if fraction.is_empty() {
+            0
+        } else {
+            let digits = fraction.len().min(12) as u32;
+            let numerator: u64 = fraction[..digits as usize].parse().ok()?;
+            (numerator as u128 * multiplier as u128 / 10u128.pow(digits)) as u64
+        };
+        total = whole
+            .checked_mul(multiplier)?
+            .checked_add(fraction)?
+            .checked_add(total)?;
+        components += 1;
+        rest = tail.trim_start();
+    }
+    (components > 0).then_some(total)
+}
+
+/// Every specifier systemd resolves in unit files.
+const KNOWN_SPECIFIERS: &str = "aAbBCdDEfgGhHiIjJlLmMnNopPqsStTuUvVwWyY%";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpecifierError {
+    Unknown(char),
+    /// A `%` at the very end of the value.
+    Incomplete,
+}
+
+impl fmt::Display for SpecifierError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown(c) => write!(f, "Unknown specifier %{}", c),
+            Self::Incomplete => f.write_str("Incomplete specifier"),
+        }
+    }
+}
+
+/// Values for specifiers such as `%i` or `%n`. Those derived from the unit
+/// name are filled in by [`Specifiers::for_unit`]; host-specific ones can be
+/// added with [`Specifiers::insert`].
+#[derive(Debug, Clone, Default)]
+pub struct Specifiers(HashMap<char, String>);
+
+impl Specifiers {
+    pub fn for_unit(unit_name: &str) -> Self {
+        let (name, _) = unit_name.rsplit_once('.').unwrap_or((unit_name, ""));
+        let (prefix, instance) = name.split_once('@').unwrap_or((name, ""));
+        let last = prefix.rsplit('-').next().unwrap_or(prefix);
+        let path = if instance.is_empty() {
+            prefix
+        } else {
+            instance
+        };
+
+        let mut specifiers = Self::default();
+        specifiers.insert('n', unit_name);
+        specifiers.insert('N', name);
+        specifiers.insert('p', prefix);
+        specifiers.insert('P', &unescape_instance(prefix));
+        specifiers.insert('i', instance);
+        specifiers.insert('I', &unescape_instance(instance));
+        specifiers.insert('j', last);
+        specifiers.insert('J', &unescape_instance(last));
+        specifiers.insert(
+            'f',
+            &format!("/{}", unescape_instance(path).trim_start_matches('/')),
+        );
+        specifiers.insert('%', "%");
+        specifiers
+    }
+
+    pub fn insert(&mut self, specifier: char, value: &str) {
+        self.0.insert(specifier, value.to_string());
+    }
+
+    /// Replaces the specifiers in `value`. Known specifiers without a value
+    /// here are kept as they are, so systemd can resolve them later.
+    pub fn expand(&self, value: &str) -> std::result::Result<String, SpecifierError> {
+        let mut expanded = String::with_capacity(value.len());
+        let mut chars = value.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                expanded.push(c);
+                continue;
+            }
+            let specifier = chars.next().ok_or(SpecifierError::Incomplete)?;
+            match self.0.get(&specifier) {
+                Some(resolved) => expanded.push_str(resolved),
+                None if KNOWN_SPECIFIERS.contains(specifier) => {
+                    expanded.push('%');
+                    expanded.push(specifier);
+                }
+                None => return Err(SpecifierError::Unknown(specifier)),
+            }
+        }
+        Ok(expanded)
+    }
+}
+
+/// The type-specific section of a unit type, e.g. `Service` for services.
+/// Targets and devices have none.
+pub fn type_section(unit_type: UnitType) -> Option<&'static str> {
+    match unit_type {
+        UnitType::Service => Some("Service"),
+        UnitType::Socket => Some("Socket"),
+        UnitType::Timer => Some("Timer"),
+        UnitType::Mount => Some("Mount"),
+        UnitType::Automount => Some("Automount"),
+        UnitType::Path => Some("Path"),
+        UnitType::Slice => Some("Slice"),
+        UnitType::Scope => Some("Scope"),
+        UnitType::Swap => Some("Swap"),
+        UnitType::Target | UnitType::Device => None,
+    }
+}
+
+fn section_applies(section: &str, unit_type: Option<UnitType>) -> bool {
+    matches!(section, "Unit" | "Install") || unit_type.and_then(type_section) == Some(section)
+}
+
+/// The key lists that apply to `section`, `None` for unknown sections.
+fn known_keys(section: &str) -> Option<&'static [&'static [&'static str]]> {
+    Some(match section {
+        "Unit" => &[UNIT_KEYS],
+        "Install" => &[INSTALL_KEYS],
+        "Service" => &[SERVICE_KEYS, EXEC_CONTEXT_KEYS, KILL_KEYS, RESOURCE_KEYS],
+        "Socket" => &[SOCKET_KEYS, EXEC_CONTEXT_KEYS, KILL_KEYS, RESOURCE_KEYS],
+        "Timer" => &[TIMER_KEYS],
+        "Mount" => &[MOUNT_KEYS, EXEC_CONTEXT_KEYS, KILL_KEYS, RESOURCE_KEYS],
+        "Automount" => &[AUTOMOUNT_KEYS],
+        "Swap" => &[SWAP_KEYS, EXEC_CONTEXT_KEYS, KILL_KEYS, RESOURCE_KEYS],
+        "Path" => &[PATH_KEYS],
+        "Slice" => &[RESOURCE_KEYS],
+        "Scope" => &[SCOPE_KEYS, KILL_KEYS, RESOURCE_KEYS],
+        _ => return None,
+    })
+}
+
+/// Keys ending in `Sec` whose value is not a plain time span;
+/// `TimerSlackNSec` takes nanoseconds.
+const TIME_SPAN_EXCEPTIONS: &[&str] = &["IODeviceLatencyTargetSec", "TimerSlackNSec"];
+
+const EXEC_KEYS: &[&str] = &[
+    "ExecCondition",
+    "ExecStartPre",
+    "ExecStart",
+    "ExecStartPost",
+    "ExecReload",
+    "ExecStop",
+    "ExecStopPre",
+    "ExecStopPost",
+];
+
+/// Suffixes of the `Condition*=` and `Assert*=` settings.
+const CONDITIONS: &[&str] = &[
+    "Architecture",
+    "Firmware",
+    "Virtualization",
+    "Host",
+    "KernelCommandLine",
+    "KernelVersion",
+    "Credential",
+    "Environment",
+    "Security",
+    "Capability",
+    "ACPower",
+    "NeedsUpdate",
+    "FirstBoot",
+    "PathExists",
+    "PathExistsGlob",
+    "PathIsDirectory",
+    "PathIsSymbolicLink",
+    "PathIsMountPoint",
+    "PathIsReadWrite",
+    "PathIsEncrypted",
+    "DirectoryNotEmpty",
+    "FileNotEmpty",
+    "FileIsExecutable",
+    "User",
+    "Group",
+    "ControlGroupController",
+    "Memory",
+    "CPUs",
+    "CPUFeature",
+    "OSRelease",
+    "MemoryPressure",
+    "CPUPressure",
+    "IOPressure",
+];
+
+const UNIT_KEYS: &[&str] = &[
+    "Description",
+    "Documentation",
+    "Wants",
+    "Requires",
+    "Requisite",
+    "BindsTo",
+    "PartOf",
+    "Upholds",
+    "Conflicts",
+    "Before",
+    "After",
+    "OnFailure",
+    "OnSuccess",
+    "PropagatesReloadTo",
+    "ReloadPropagatedFrom",
+    "PropagatesStopTo",
+    "StopPropagatedFrom",
+    "JoinsNamespaceOf",
+    "RequiresMountsFor",
+    "WantsMountsFor",
+    "OnFailureJobMode",
+    "OnSuccessJobMode",
+    "IgnoreOnIsolate",
+    "StopWhenUnneeded",
+    "RefuseManualStart",
+    "RefuseManualStop",
+    "AllowIsolate",
+    "DefaultDependencies",
+    "SurviveFinalKillSignal",
+    "CollectMode",
+    "FailureAction",
+    "SuccessAction",
+    "FailureActionExitStatus",
+    "SuccessActionExitStatus",
+    "JobTimeoutSec",
+    "JobRunningTimeoutSec",
+    "JobTimeoutAction",
+    "JobTimeoutRebootArgument",
+    "StartLimitIntervalSec",
+    "StartLimitBurst",
+    "StartLimitAction",
+    "RebootArgument",
+    "SourcePath",
+];
+
+const INSTALL_KEYS: &[&str] = &[
+    "Alias",
+    "WantedBy",
+    "RequiredBy",
+    "UpheldBy",
+    "Also",
+    "DefaultInstance",
+];
+
+/// Settings from systemd.exec(5), shared by units that spawn processes.
+const EXEC_CONTEXT_KEYS: &[&str] = &[
+    "WorkingDirectory",
+    "RootDirectory",
+    "RootImage",
+    "RootImageOptions",
+    "RootEphemeral",
+    "RootHash",
+    "RootHashSignature",
+    "RootVerity",
+    "RootImagePolicy",
+    "MountImagePolicy",
+    "ExtensionImagePolicy",
+    "MountAPIVFS",
+    "ProtectProc",
+    "ProcSubset",
+    "BindPaths",
+    "BindReadOnlyPaths",
+    "MountImages",
+    "ExtensionImages",
+    "ExtensionDirectories",
+    "User",
+    "Group",
+    "DynamicUser",
+    "SupplementaryGroups",
+    "SetLoginEnvironment",
+    "PAMName",
+    "CapabilityBoundingSet",
+    "AmbientCapabilities",
+    "NoNewPrivileges",
+    "SecureBits",
+    "SELinuxContext",
+    "AppArmorProfile",
+    "SmackProcessLabel",
+    "LimitCPU",
+    "LimitFSIZE",
+    "LimitDATA",
+    "LimitSTACK",
+    "LimitCORE",
+    "LimitRSS",
+    "LimitNOFILE",
+    "LimitAS",
+    "LimitNPROC",
+    "LimitMEMLOCK",
+    "LimitLOCKS",
+    "LimitSIGPENDING",
+    "LimitMSGQUEUE",
+    "LimitNICE",
+    "LimitRTPRIO",
+    "LimitRTTIME",
+    "UMask",
+    "CoredumpFilter",
+    "KeyringMode",
+    "OOMScoreAdjust",
+    "TimerSlackNSec",
+    "Personality",
+    "IgnoreSIGPIPE",
+    "Nice",
+    "CPUSchedulingPolicy",
+    "CPUSchedulingPriority",
+    "CPUSchedulingResetOnFork",
+    "CPUAffinity",
+    "NUMAPolicy",
+    "NUMAMask",
+    "IOSchedulingClass",
+    "IOSchedulingPriority",
+    "ProtectSystem",
+    "ProtectHome",
+    "RuntimeDirectory",
+    "StateDirectory",
+    "CacheDirectory",
+    "LogsDirectory",
+    "ConfigurationDirectory",
+    "RuntimeDirectoryMode",
+    "StateDirectoryMode",
+    "CacheDirectoryMode",
+    "LogsDirectoryMode",
+    "ConfigurationDirectoryMode",
+    "RuntimeDirectoryPreserve",
+    "TimeoutCleanSec",
+    "ReadWritePaths",
+    "ReadOnlyPaths",
+    "InaccessiblePaths",
+    "ExecPaths",
+    "NoExecPaths",
+    "TemporaryFileSystem",
+    "PrivateTmp",
+    "PrivateDevices",
+    "PrivateNetwork",
+    "NetworkNamespacePath",
+    "PrivateIPC",
+    "IPCNamespacePath",
+    "MemoryKSM",
+    "PrivateUsers",
+    "ProtectHostname",
+    "ProtectClock",
+    "ProtectKernelTunables",
+    "ProtectKernelModules",
+    "ProtectKernelLogs",
+    "ProtectControlGroups",
+    "RestrictAddressFamilies",
+    "RestrictFileSystems",
+    "RestrictNamespaces",
+    "LockPersonality",
+    "MemoryDenyWriteExecute",
+    "RestrictRealtime",
+    "RestrictSUIDSGID",
+    "RemoveIPC",
+    "PrivateMounts",
+    "MountFlags",
+    "SystemCallFilter",
+    "SystemCallErrorNumber",
+    "SystemCallArchitectures",
+    "SystemCallLog",
+    "Environment",
+    "EnvironmentFile",
+    "PassEnvironment",
+    "UnsetEnvironment",
+    "StandardInput",
+    "StandardOutput",
+    "StandardError",
+    "StandardInputText",
+    "StandardInputData",
+    "LogLevelMax",
+    "LogExtraFields",
+    "LogRateLimitIntervalSec",
+    "LogRateLimitBurst",
+    "LogFilterPatterns",
+    "LogNamespace",
+    "SyslogIdentifier",
+    "SyslogFacility",
+    "SyslogLevel",
+    "SyslogLevelPrefix",
+    "TTYPath",
+    "TTYReset",
+    "TTYVHangup",
+    "TTYRows",
+    "TTYColumns",
+    "TTYVTDisallocate",
+    "LoadCredential",
+    "LoadCredentialEncrypted",
+    "ImportCredential",
+    "SetCredential",
+    "SetCredentialEncrypted",
+    "UtmpIdentifier",
+    "UtmpMode",
+];
+
+/// Settings from systemd.kill(5).
+const KILL_KEYS: &[&str] = &[
+    "KillMode",
+    "KillSignal",
+    "RestartKillSignal",
+    "SendSIGHUP",
+    "SendSIGKILL",
+    "FinalKillSignal",
+    "WatchdogSignal",
+];
+
+/// Settings from systemd.resource-control(5).
+const RESOURCE_KEYS: &[&str] = &[
+    "Slice",
+    "Delegate",
+    "DelegateSubgroup",
+    "CPUAccounting",
+    "CPUWeight",
+    "StartupCPUWeight",
+    "CPUQuota",
+    "CPUQuotaPeriodSec",
+    "AllowedCPUs",
+    "StartupAllowedCPUs",
+    "AllowedMemoryNodes",
+    "StartupAllowedMemoryNodes",
+    "MemoryAccounting",
+    "MemoryMin",
+    "MemoryLow",
+    "StartupMemoryLow",
+    "DefaultStartupMemoryLow",
+    "MemoryHigh",
+    "StartupMemoryHigh",
+    "MemoryMax",
+    "StartupMemoryMax",
+    "MemorySwapMax",
+    "StartupMemorySwapMax",
+    "MemoryZSwapMax",
+    "StartupMemoryZSwapMax",
+    "MemoryZSwapWriteback",
+    "TasksAccounting",
+    "TasksMax",
+    "IOAccounting",
+    "IOWeight",
+    "StartupIOWeight",
+    "IODeviceWeight",
+    "IOReadBandwidthMax",
+    "IOWriteBandwidthMax",
+    "IOReadIOPSMax",
+    "IOWriteIOPSMax",
+    "IODeviceLatencyTargetSec",
+    "IPAccounting",
+    "IPAddressAllow",
+    "IPAddressDeny",
+    "SocketBindAllow",
+    "SocketBindDeny",
+    "RestrictNetworkInterfaces",
+    "NFTSet",
+    "IPIngressFilterPath",
+    "IPEgressFilterPath",
+    "BPFProgram",
+    "DeviceAllow",
+    "DevicePolicy",
+    "ManagedOOMSwap",
+    "ManagedOOMMemoryPressure",
+    "ManagedOOMMemoryPressureLimit",
+    "ManagedOOMPreference",
+    "MemoryPressureWatch",
+    "MemoryPressureThresholdSec",
+    "CoredumpReceive",
+    "DisableControllers",
+    "CPUShares",
+    "StartupCPUShares",
+    "MemoryLimit",
+];
+
+const SERVICE_KEYS: &[&str] = &[
+    "Type",
+    "ExitType",
+    "RemainAfterExit",
+    "GuessMainPID",
+    "PIDFile",
+    "BusName",
+    "ExecCondition",
+    "ExecStartPre",
+    "ExecStart",
+    "ExecStartPost",
+    "ExecReload",
+    "ExecStop",
+    "ExecStopPost",
+    "RestartSec",
+    "RestartSteps",
+    "RestartMaxDelaySec",
+    "TimeoutStartSec",
+    "TimeoutStopSec",
+    "TimeoutAbortSec",
+    "TimeoutSec",
+    "TimeoutStartFailureMode",
+    "TimeoutStopFailureMode",
+    "RuntimeMaxSec",
+    "RuntimeRandomizedExtraSec",
+    "WatchdogSec",
+    "Restart",
+    "RestartMode",
+    "SuccessExitStatus",
+    "RestartPreventExitStatus",
+    "RestartForceExitStatus",
+    "RootDirectoryStartOnly",
+    "NonBlocking",
+    "NotifyAccess",
+    "Sockets",
+    "FileDescriptorStoreMax",
+    "FileDescriptorStorePreserve",
+    "USBFunctionDescriptors",
+    "USBFunctionStrings",
+    "OOMPolicy",
+    "OpenFile",
+    "ReloadSignal",
+];
+
+const SOCKET_KEYS: &[&str] = &[
+    "ListenStream",
+    "ListenDatagram",
+    "ListenSequentialPacket",
+    "ListenFIFO",
+    "ListenSpecial",
+    "ListenNetlink",
+    "ListenMessageQueue",
+    "ListenUSBFunction",
+    "SocketProtocol",
+    "BindIPv6Only",
+    "Backlog",
+    "BindToDevice",
+    "SocketUser",
+    "SocketGroup",
+    "SocketMode",
+    "DirectoryMode",
+    "Accept",
+    "Writable",
+    "FlushPending",
+    "MaxConnections",
+    "MaxConnectionsPerSource",
+    "KeepAlive",
+    "KeepAliveTimeSec",
+    "KeepAliveIntervalSec",
+    "KeepAliveProbes",
+    "NoDelay",
+    "Priority",
+    "DeferAcceptSec",
+    "ReceiveBuffer",
+    "SendBuffer",
+    "IPTOS",
+    "IPTTL",
+    "Mark",
+    "ReusePort",
+    "SmackLabel",
+    "SmackLabelIPIn",
+    "SmackLabelIPOut",
+    "SELinuxContextFromNet",
+    "PipeSize",
+    "MessageQueueMaxMessages",
+    "MessageQueueMessageSize",
+    "FreeBind",
+    "Transparent",
+    "Broadcast",
+    "PassCredentials",
+    "PassSecurity",
+    "PassPacketInfo",
+    "Timestamping",
+    "TCPCongestion",
+    "ExecStartPre",
+    "ExecStartPost",
+    "ExecStopPre",
+    "ExecStopPost",
+    "TimeoutSec",
+    "Service",
+    "RemoveOnStop",
+    "Symlinks",
+    "FileDescriptorName",
+    "TriggerLimitIntervalSec",
+    "TriggerLimitBurst",
+    "PollLimitIntervalSec",
+    "PollLimitBurst",
+    "PassFileDescriptorsToExec",
+];
+
+const TIMER_KEYS: &[&str] = &[
+    "OnActiveSec",
+    "OnBootSec",
+    "OnStartupSec",
+    "OnUnitActiveSec",
+    "OnUnitInactiveSec",
+    "OnCalendar",
+    "AccuracySec",
+    "RandomizedDelaySec",
+    "RandomizedOffsetSec",
+    "FixedRandomDelay",
+    "OnClockChange",
+    "OnTimezoneChange",
+    "Unit",
+    "Persistent",
+    "WakeSystem",
+    "RemainAfterElapse",
+];
+
+const MOUNT_KEYS: &[&str] = &[
+    "What",
+    "Where",
+    "Type",
+    "Options",
+    "SloppyOptions",
+    "LazyUnmount",
+    "ReadWriteOnly",
+    "ForceUnmount",
+    "DirectoryMode",
+    "TimeoutSec",
+];
+
+const AUTOMOUNT_KEYS: &[&str] = &["Where", "ExtraOptions", "DirectoryMode", "TimeoutIdleSec"];
+
+const SWAP_KEYS: &[&str] = &["What", "Priority", "Options", "TimeoutSec"];
+
+const PATH_KEYS: &[&str] = &[
+    "PathExists",
+    "PathExistsGlob",
+    "PathChanged",
+    "PathModified",
+    "DirectoryNotEmpty",
+    "Unit",
+    "MakeDirectory",
+    "DirectoryMode",
+    "TriggerLimitIntervalSec",
+    "TriggerLimitBurst",
+];
+
+const SCOPE_KEYS: &[&str] = &["RuntimeMaxSec", "RuntimeRandomizedExtraSec", "OOMPolicy"];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEMO: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/unitfile/demo.service"
+    ));
+    /// [`DEMO`] as [`UnitFile`] prints it back.
+    const DEMO_CANONICAL: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/unitfile/demo.canonical.service"
+    ));
+    const EXEC: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/unitfile/exec.service"
+    ));
+    const BROKEN: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/unitfile/broken.service"
+    ));
+
+    /// The file with every line number cleared, to compare files read from
+    /// different text.
+    fn without_lines(file: &UnitFile) -> UnitFile {
+        let mut file = file.clone();
+        for section in &mut file.sections {
+            section.line = 0;
+            for entry in &mut section.entries {
+                entry.line = 0;
+            }
+        }
+        file
+    }
+
+    #[test]
+    fn parse_keeps_repeated_sections_and_keys() {
+        let file = UnitFile::parse(DEMO);
+        let sections: Vec<(&str, usize)> = file
+            .sections
+            .iter()
+            .map(|section| (section.name.as_str(), section.line))
+            .collect();
+        assert_eq!(
+            sections,
+            [("Unit", 2), ("Service", 7), ("Unit", 15), ("Install", 20)]
+        );
+        assert_eq!(
+            file.values("Unit", "After"),
+            ["network.target", "time-sync.target"]
+        );
+        assert_eq!(file.value("Unit", "Description"), Some("Demo for %n"));
+        assert_eq!(
+            file.value("Service", "Environment"),
+            Some("\"GREETING=hello world\"")
+        );
+        assert_eq!(
+            file.sections[0].comments,
+            ["# Demo service used by the parser tests."]
+        );
+        assert_eq!(file.sections[3].comments, ["; closing remark"]);
+        assert_eq!(file.trailing_comments, ["# trailing comment"]);
+        assert!(file.lint("demo.service", FileKind::Fragment).is_empty());
+    }
+
+    #[test]
+    fn parse_joins_continuations_and_drops_comments_inside() {
+        let file = UnitFile::parse(DEMO);
+        let exec_start: Vec<&Entry> = file.entries("Service", "ExecStart").collect();
+        assert_eq!(exec_start.len(), 1);
+        assert_eq!(exec_start[0].value, "/usr/bin/demo --verbose --port=8080");
+        assert_eq!(exec_start[0].line, 9);
+        assert!(exec_start[0].comments.is_empty());
+        assert_eq!(
+            file.entries("Service", "Environment").next().unwrap().line,
+            13
+        );
+    }
+
+    #[test]
+    fn values_are_cleared_by_an_empty_assignment() {
+        let file = UnitFile::parse("[Unit]\nAfter=a.target\nAfter=\nAfter=b.target\n");
+        assert_eq!(file.values("Unit", "After"), ["b.target"]);
+    }
+
+    #[test]
+    fn display_round_trips_parse() {
+        let file = UnitFile::parse(DEMO);
+        assert_eq!(file.to_string(), DEMO_CANONICAL);
+
+        let reparsed = UnitFile::parse(&file.to_string());
+        assert_eq!(without_lines(&reparsed), without_lines(&file));
+        assert_eq!(reparsed.to_string(), DEMO_CANONICAL);
+
+        let canonical = UnitFile::parse(DEMO_CANONICAL);
+        assert_eq!(canonical, reparsed);
+    }
+
+    #[test]
+    fn specifiers_expand_from_the_unit_name() {
+        let specifiers = Specifiers::for_unit("getty@tty1.service");
+        assert_eq!(
+            specifiers.expand("%n on %i at %f").unwrap(),
+            "getty@tty1.service on tty1 at /tty1"
+        );
+        assert_eq!(specifiers.expand("%p %N").unwrap(), "getty getty@tty1");
+
+        let specifiers = Specifiers::for_unit("systemd-fsck@dev-disk-by\\x2dlabel-root.service");
+        assert_eq!(specifiers.expand("%I").unwrap(), "dev/disk/by-label/root");
+        assert_eq!(specifiers.expand("%f").unwrap(), "/dev/disk/by-label/root");
+        assert_eq!(specifiers.expand("%j").unwrap(), "fsck");
+
+        let specifiers = Specifiers::for_unit("demo.service");
+        assert_eq!(specifiers.expand("%i|%f|100%%").unwrap(), "|/demo|100%");
+    }
+
+    #[test]
+    fn specifiers_reject_unknown_and_keep_unresolved() {
+        let mut specifiers = Specifiers::for_unit("demo.service");
+        assert_eq!(specifiers.expand("%z"), Err(SpecifierError::Unknown('z')));
+        assert_eq!(specifiers.expand("50%"), Err(SpecifierError::Incomplete));
+        assert_eq!(specifiers.expand("%H/%u").unwrap(), "%H/%u");
+        specifiers.insert('H', "host");
+        assert_eq!(specifiers.expand("%H/%u").unwrap(), "host/%u");
+    }
+
+    #[test]
+    fn parse_exec_reads_every_form() {
+        let file = UnitFile::parse(EXEC);
+        let lines: Vec<Vec<ExecCommand>> = file
+            .values("Service", "ExecStart")
+            .into_iter()
+            .map(|value| parse_exec(value).unwrap())
+            .collect();
+        let command = |prefixes: &str, path: &str, argv: &[&str]| ExecCommand {
+            prefixes: prefixes.to_string(),
+            path: path.to_string(),
+            argv: argv.iter().map(|arg| arg.to_string()).collect(),
+        };
+        assert_eq!(
+            lines,
+            [
+                vec![command(
+                    "",
+                    "/usr/bin/printf",
+                    &["/usr/bin/printf", "%%s\n", "hello world"]
+                )],
+                vec![command("@", "/usr/bin/sleep", &["sleeper", "1"])],
+                vec![
+                    command("", "/bin/echo", &["/bin/echo", "one"]),
+                    command("-", "/bin/echo", &["/bin/echo", "two"]),
+                    command("", "/bin/echo", &["/bin/echo", ";"]),
+                    command("", "/bin/echo", &["/bin/echo", ";", "escaped"]),
+                ],
+            ]
+        );
+        // Several commands are fine for a oneshot service, and
+        // TimerSlackNSec= is not read as a time span in seconds.
+        assert_eq!(
+            file.lint("exec.service", FileKind::Fragment),
+            [Diagnostic::new(
+                None,
+                Severity::Info,
+                "No [Install] section, so the unit cannot be enabled"
+            )]
+        );
+    }
+
+    #[test]
+    fn parse_exec_rejects_broken_commands() {
+        assert_eq!(
+            parse_exec("@/bin/true"),
+            Err("/bin/true needs an argv[0] after the path".to_string())
+        );
+        assert_eq!(
+            parse_exec("/bin/echo \"open"),
+            Err("Unterminated quote".to_string())
+        );
+        assert_eq!(
+            parse_exec("- arg"),
+            Err("Missing executable path".to_string())
+        );
+        assert_eq!(
+            parse_exec("/bin/echo \\q"),
+            Err("Invalid escape \\q".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_time_span_reads_systemd_spans() {
+        assert_eq!(parse_time_span("1min 30s"), Some(90_000_000));
+        assert_eq!(parse_time_span("90"), Some(90_000_000));
+        assert_eq!(parse_time_span("1.5s"), Some(1_500_000));
+        assert_eq!(parse_time_span("2h15min"), Some(8_100_000_000));
+        assert_eq!(parse_time_span("infinity"), Some(u64::MAX));
+        assert_eq!(parse_time_span("5x"), None);
+        assert_eq!(parse_time_span(""), None);
+    }
+
+    #[test]
+    fn lint_reports_each_problem() {
+        let file = UnitFile::parse(BROKEN);
+        let diagnostics = file.lint("broken.service", FileKind::Fragment);
+        assert_eq!(
+            diagnostics,
+            [
+                Diagnostic::new(
+                    None,
+                    Severity::Info,
+                    "No [Install] section, so the unit cannot be enabled"
+                ),
+                Diagnostic::new(
+                    Some(3),
+                    Severity::Warning,
+                    "Unknown key Frobnicate in [Unit], ignoring"
+                ),
+                Diagnostic::new(
+                    Some(5),
+                    Severity::Error,
+                    "Service has more than one ExecStart= command, which only Type=oneshot allows"
+                ),
+                Diagnostic::new(
+                    Some(7),
+                    Severity::Error,
+                    "ExecStart= path bin/demo is relative, use an absolute path"
+                ),
+                Diagnostic::new(
+                    Some(9),
+                    Severity::Error,
+                    "Invalid time span TimeoutStartSec=5x"
+                ),
+                Diagnostic::new(
+                    Some(11),
+                    Severity::Error,
+                    "Unknown specifier %z in ExecStartPost=/usr/bin/notify %z"
+                ),
+            ]
+        );
+        assert_eq!(
+            diagnostics[3].to_string(),
+            "Line 7: ExecStart= path bin/demo is relative, use an absolute path"
+        );
+    }
+
+    #[test]
+    fn lint_skips_required_settings_in_drop_ins() {
+        let file = UnitFile::parse(BROKEN);
+        let diagnostics = file.lint("broken.service", FileKind::DropIn);
+        let lines: Vec<Option<usize>> = diagnostics.iter().map(|d| d.line).collect();
+        assert_eq!(lines, [Some(3), Some(7), Some(9), Some(11)]);
+    }
+
+    #[test]
+    fn lint_reports_syntax_errors() {
+        let file = UnitFile::parse("Orphan=1\n[Unit\n[Unit]\nNoEquals\n=value\n");
+        let diagnostics = file.lint("demo.target", FileKind::DropIn);
+        let messages: Vec<String> = diagnostics.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            messages,
+            [
+                "Line 1: Orphan is set outside of any section",
+                "Line 2: Invalid section header [Unit",
+                "Line 4: Missing '=' in \"NoEquals\"",
+                "Line 5: Assignment without a key",
+            ]
+        );
+        assert!(diagnostics.iter().all(|d| d.severity == Severity::Error));
+    }
+}
@@ -0,0 +1,75 @@
+//! Runs blocking backend calls on a worker thread.
+//!
+//! Every D-Bus call in the backend is blocking, and a polkit prompt or a hung
+//! job can take arbitrarily long. [`spawn`] moves such a call to its own
+//! thread and hands back a future, so the GTK main loop can `.await` it from
+//! `glib::MainContext::spawn_local` without freezing the window.
+
+use super::SystemdServiceManager;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+type Outcome<T> = std::thread::Result<T>;
+
+struct Shared<T> {
+    outcome: Option<Outcome<T>>,
+    waker: Option<Waker>,
+}
+
+/// The pending result of a call started with [`spawn`]. A panic on the
+/// worker thread is resumed when the task is polled.
+pub struct Task<T> {
+    shared: Arc<Mutex<Shared<T>>>,
+}
+
+impl<T> Future for Task<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut shared = self.shared.lock().unwrap_or_else(|e| e.into_inner());
+        match shared.outcome.take() {
+            Some(Ok(value)) => Poll::Ready(value),
+            Some(Err(payload)) => panic::resume_unwind(payload),
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+pub fn spawn<T, F>(f: F) -> Task<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let shared = Arc::new(Mutex::new(Shared {
+        outcome: None,
+        waker: None,
+    }));
+    let worker_shared = Arc::clone(&shared);
+    std::thread::spawn(move || {
+        let outcome = panic::catch_unwind(AssertUnwindSafe(f));
+        let mut shared = worker_shared.lock().unwrap_or_else(|e| e.into_inner());
+        shared.outcome = Some(outcome);
+        if let Some(waker) = shared.waker.take() {
+            waker.wake();
+        }
+    });
+    Task { shared }
+}
+
+impl SystemdServiceManager {
+    /// Runs `f` against a clone of the manager on a worker thread.
+    pub fn run<T, F>(&self, f: F) -> Task<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&SystemdServiceManager) -> T + Send + 'static,
+    {
+        let manager = self.clone();
+        spawn(move || f(&manager))
+    }
+}
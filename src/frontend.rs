@@ -1,18 +1,51 @@
-use crate::backend::{EnablementStatus, ServiceInfo, ServiceStatus, SystemdServiceManager};
+use crate::backend::events::ServiceEvent;
+use crate::backend::jobs::JobResult;
+use crate::backend::templates;
+use crate::backend::{
+    EnablementStatus, ServiceStatus, SystemdServiceManager, UnitFileChanges, UnitFileOptions,
+    UnitInfo, UnitScope, UnitType, UnitTypeInfo,
+};
 use adw::{Application, HeaderBar, Toast, ToastOverlay, ToastPriority, Window, prelude::*};
 use gtk4::{
-    Align, Box, Button, ComboBoxText, Justification, Label, ListBox, ListBoxRow, Orientation,
-    PolicyType, ScrolledWindow, SearchEntry, Separator,
+    Align, Box, Button, CheckButton, ComboBoxText, Justification, Label, ListBox, ListBoxRow,
+    Orientation, PolicyType, ScrolledWindow, SearchEntry, Separator, Spinner, glib,
 };
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
 use std::rc::Rc;
+use std::time::Duration;
+
+mod boot;
+mod dependencies;
+mod detail;
+mod failed;
+mod instance;
+mod jobs;
+mod logs;
+mod new_service;
+mod processes;
+mod signal;
+mod timers;
+mod unit_file;
+use boot::BootView;
+use detail::DetailPane;
+use failed::FailedView;
+use jobs::JobsPanel;
+use timers::TimersView;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ServiceAction {
     Start,
     Stop,
+    Restart,
+    Reload,
+    TryRestart,
+    ReloadOrRestart,
     Enable,
     Disable,
+    Mask,
+    Unmask,
+    ResetFailed,
 }
 
 impl ServiceAction {
@@ -20,17 +53,100 @@ impl ServiceAction {
         match self {
             ServiceAction::Start => "Start",
             ServiceAction::Stop => "Stop",
+            ServiceAction::Restart => "Restart",
+            ServiceAction::Reload => "Reload",
+            ServiceAction::TryRestart => "Try Restart",
+            ServiceAction::ReloadOrRestart => "Reload or Restart",
             ServiceAction::Enable => "Enable",
             ServiceAction::Disable => "Disable",
+            ServiceAction::Mask => "Mask",
+            ServiceAction::Unmask => "Unmask",
+            ServiceAction::ResetFailed => "Reset Failed",
+        }
+    }
+
+    pub fn verb(&self) -> &str {
+        match self {
+            ServiceAction::Start => "start",
+            ServiceAction::Stop => "stop",
+            ServiceAction::Restart => "restart",
+            ServiceAction::Reload => "reload",
+            ServiceAction::TryRestart => "try-restart",
+            ServiceAction::ReloadOrRestart => "reload or restart",
+            ServiceAction::Enable => "enable",
+            ServiceAction::Disable => "disable",
+            ServiceAction::Mask => "mask",
+            ServiceAction::Unmask => "unmask",
+            ServiceAction::ResetFailed => "reset the failed state of",
+        }
+    }
+
+    pub fn past_tense(&self) -> &str {
+        match self {
+            ServiceAction::Start => "Started",
+            ServiceAction::Stop => "Stopped",
+            ServiceAction::Restart => "Restarted",
+            ServiceAction::Reload => "Reloaded",
+            ServiceAction::TryRestart => "Try-restarted",
+            ServiceAction::ReloadOrRestart => "Reloaded or restarted",
+            ServiceAction::Enable => "Enabled",
+            ServiceAction::Disable => "Disabled",
+            ServiceAction::Mask => "Masked",
+            ServiceAction::Unmask => "Unmasked",
+            ServiceAction::ResetFailed => "Reset the failed state of",
+        }
+    }
+}
+
+/// A button of the actions panel. Besides the [`ServiceAction`]s run on the
+/// selection there are ones that only open a dialog; those never reach
+/// [`ServiceManagerState::run_service_action`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PanelAction {
+    Service(ServiceAction),
+    NewInstance,
+    Kill,
+}
+
+impl PanelAction {
+    pub fn label(&self) -> &str {
+        match self {
+            PanelAction::Service(action) => action.label(),
+            PanelAction::NewInstance => "New Instance…",
+            PanelAction::Kill => "Send Signal…",
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ServiceData {
     pub name: String,
+    pub unit_type: UnitType,
+    pub description: String,
     pub status: ServiceStatus,
+    pub sub_state: String,
+    pub load_state: String,
     pub enablement: EnablementStatus,
+    pub scope: UnitScope,
+    pub job: Option<(u32, String)>,
+    pub type_info: Option<UnitTypeInfo>,
+}
+
+impl From<&UnitInfo> for ServiceData {
+    fn from(service: &UnitInfo) -> Self {
+        Self {
+            name: service.name.clone(),
+            unit_type: service.unit_type,
+            description: service.description.clone(),
+            status: service.status.clone(),
+            sub_state: service.sub_state.clone(),
+            load_state: service.load_state.clone(),
+            enablement: service.enablement_status.clone(),
+            scope: service.scope,
+            job: service.job.clone(),
+            type_info: service.type_info.clone(),
+        }
+    }
 }
 
 impl ServiceData {
@@ -38,141 +154,798 @@ impl ServiceData {
         query.is_empty() || self.name.to_lowercase().contains(&query.to_lowercase())
     }
 
-    pub fn matches_filters(&self, status_filter: &str, enablement_filter: &str) -> bool {
+    pub fn matches_filters(
+        &self,
+        type_filter: &str,
+        status_filter: &str,
+        enablement_filter: &str,
+        scope_filter: &str,
+    ) -> bool {
+        let type_matches = type_filter == "All" || format_unit_type(self.unit_type) == type_filter;
         let status_matches = status_filter == "All" || format_status(&self.status) == status_filter;
         let enablement_matches =
             enablement_filter == "All" || format_enablement(&self.enablement) == enablement_filter;
-        status_matches && enablement_matches
+        let scope_matches = scope_filter == "All" || format_scope(self.scope) == scope_filter;
+        type_matches && status_matches && enablement_matches && scope_matches
     }
 }
 
+#[derive(Clone)]
 pub struct ServiceManagerState {
     pub systemd: SystemdServiceManager,
     pub service_widgets: Rc<RefCell<Vec<(ServiceData, ListBoxRow)>>>,
     pub services_list: ListBox,
+    pub type_combo: ComboBoxText,
     pub status_combo: ComboBoxText,
     pub enablement_combo: ComboBoxText,
+    pub scope_combo: ComboBoxText,
     pub current_query: Rc<RefCell<String>>,
     pub toast_overlay: ToastOverlay,
+    pub action_buttons: Vec<(PanelAction, Button)>,
+    pub runtime_toggle: CheckButton,
+    pub daemon_reload_switch: adw::SwitchRow,
+    pub detail_pane: Rc<DetailPane>,
+    /// Rows with an operation in flight, keyed by name and scope.
+    pub busy_services: Rc<RefCell<HashSet<(String, UnitScope)>>>,
+    /// Bumped per selection change, so a late `CanReload` reply for an
+    /// earlier selection is dropped.
+    pub sensitivity_generation: Rc<Cell<u64>>,
+    pub jobs_panel: Rc<JobsPanel>,
+    pub timers_view: Rc<TimersView>,
+    pub failed_view: Rc<FailedView>,
+    pub boot_view: Rc<BootView>,
 }
 
 impl ServiceManagerState {
     pub fn refresh_services(&self) {
-        // Step 1: Take all widgets, releasing the borrow immediately
-        let old_widgets = {
-            let mut widgets = self.service_widgets.borrow_mut();
-            std::mem::take(&mut *widgets)
-        };
-
-        // Step 2: Process the old widgets outside the borrow
-        for (_, row) in old_widgets {
-            self.services_list.remove(&row);
-        }
+        let state = self.clone();
+        glib::spawn_future_local(async move {
+            let services = state.systemd.run(|systemd| systemd.get_units()).await;
+
+            // Step 1: Take all widgets, releasing the borrow immediately
+            let old_widgets = {
+                let mut widgets = state.service_widgets.borrow_mut();
+                std::mem::take(&mut *widgets)
+            };
+
+            // Step 2: Process the old widgets outside the borrow
+            for (_, row) in old_widgets {
+                state.services_list.remove(&row);
+            }
 
-        if let Ok(services) = self.systemd.get_services() {
-            let new_widgets: Vec<(ServiceData, ListBoxRow)> = services
-                .into_iter()
-                .map(|service| create_service_entry(&service))
-                .collect();
+            if let Ok(services) = services {
+                let busy = state.busy_services.borrow();
+                let new_widgets: Vec<(ServiceData, ListBoxRow)> = services
+                    .iter()
+                    .map(|service| {
+                        let (data, row) = create_service_entry(service);
+                        if busy.contains(&(data.name.clone(), data.scope)) {
+                            row.set_child(Some(&create_service_row_content(&data, true)));
+                        }
+                        (data, row)
+                    })
+                    .collect();
+
+                for (_, row) in &new_widgets {
+                    state.services_list.append(row);
+                }
 
-            for (_, row) in &new_widgets {
-                self.services_list.append(row);
+                // Step 3: Assign the new widgets (short borrow again)
+                *state.service_widgets.borrow_mut() = new_widgets;
             }
 
-            // Step 3: Assign the new widgets (short borrow again)
-            *self.service_widgets.borrow_mut() = new_widgets;
-        }
-
-        self.update_visibility();
+            state.update_visibility();
+            state.update_action_sensitivity();
+        });
     }
 
     pub fn update_visibility(&self) {
         let query = self.current_query.borrow().clone();
+        let type_filter = self.type_combo.active_text().unwrap_or("All".into());
         let status_filter = self.status_combo.active_text().unwrap_or("All".into());
         let enablement_filter = self.enablement_combo.active_text().unwrap_or("All".into());
+        let scope_filter = self.scope_combo.active_text().unwrap_or("All".into());
 
         update_service_visibility(
             &self.service_widgets.borrow(),
             &query,
+            &type_filter,
             &status_filter,
             &enablement_filter,
+            &scope_filter,
         );
     }
 
-    pub fn handle_service_action(&self, action: ServiceAction) {
-        let selected_services = get_selected_services(&self.services_list);
+    fn selected_services(&self) -> Vec<ServiceData> {
+        get_selected_services(&self.services_list, &self.service_widgets.borrow())
+    }
+
+    /// Disables actions the selected unit cannot perform. Only Reload depends
+    /// on the unit: systemd rejects it for units without `CanReload`.
+    pub fn update_action_sensitivity(&self) {
+        let selected_services = self.selected_services();
+        let template_selected = matches!(
+            selected_services.as_slice(),
+            [service] if templates::is_template(&service.name)
+        );
+        for (action, button) in &self.action_buttons {
+            if *action == PanelAction::NewInstance {
+                button.set_sensitive(template_selected);
+            }
+        }
+
+        let generation = self.sensitivity_generation.get() + 1;
+        self.sensitivity_generation.set(generation);
+        let selected = unit_keys(&selected_services);
+        let state = self.clone();
+        glib::spawn_future_local(async move {
+            // Nothing selected or an error reading the property both leave
+            // Reload disabled rather than offering an action that fails.
+            let can_reload = !selected.is_empty()
+                && state
+                    .systemd
+                    .run(move |systemd| {
+                        selected
+                            .iter()
+                            .all(|(name, scope)| systemd.can_reload(name, *scope).unwrap_or(false))
+                    })
+                    .await;
+            if state.sensitivity_generation.get() != generation {
+                return;
+            }
+            for (action, button) in &state.action_buttons {
+                if *action == PanelAction::Service(ServiceAction::Reload) {
+                    button.set_sensitive(can_reload);
+                }
+            }
+        });
+    }
+
+    pub fn show_selected_details(&self) {
+        self.detail_pane.set_unit(
+            self.selected_services()
+                .first()
+                .map(|service| (service.name.clone(), service.scope)),
+        );
+        self.refresh_selected_properties();
+    }
+
+    /// Re-reads the properties tab only, leaving the journal tab alone. The
+    /// reply is dropped if the selection changed while it was in flight.
+    pub fn refresh_selected_properties(&self) {
+        let Some(service) = self.selected_services().into_iter().next() else {
+            self.detail_pane.clear();
+            return;
+        };
+        let state = self.clone();
+        glib::spawn_future_local(async move {
+            let (name, scope) = (service.name.clone(), service.scope);
+            let details = state
+                .systemd
+                .run(move |systemd| systemd.get_unit_details(&name, scope))
+                .await;
+            let still_selected = state
+                .selected_services()
+                .first()
+                .is_some_and(|s| s.name == service.name && s.scope == service.scope);
+            if !still_selected {
+                return;
+            }
+            match details {
+                Ok(details) => state.detail_pane.show_details(&details),
+                Err(e) => state.detail_pane.show_error(&e.to_string()),
+            }
+        });
+    }
+
+    pub fn handle_panel_action(&self, action: PanelAction) {
+        let selected_services = self.selected_services();
         if selected_services.is_empty() {
             self.show_toast("No services selected", ToastPriority::Normal);
             return;
         }
+        let action = match action {
+            PanelAction::Service(action) => action,
+            PanelAction::NewInstance => {
+                self.new_instance(&selected_services[0]);
+                return;
+            }
+            PanelAction::Kill => {
+                self.send_signal(selected_services);
+                return;
+            }
+        };
+        if action == ServiceAction::Stop {
+            self.confirm_stop(selected_services);
+            return;
+        }
+        self.run_service_action(action, unit_keys(&selected_services));
+    }
 
-        for service_name in &selected_services {
-            match action {
-                ServiceAction::Start => {
-                    if let Err(e) = self.systemd.start_unit(service_name) {
-                        self.show_toast(
-                            &format!("Failed to start {}: {}", service_name, e),
-                            ToastPriority::High,
-                        );
-                    } else {
-                        self.show_toast(
-                            &format!("Started {}", service_name),
+    /// Asks for a signal and sends it to the services' processes.
+    fn send_signal(&self, services: Vec<ServiceData>) {
+        let names: Vec<String> = services.iter().map(|s| s.name.clone()).collect();
+        let state = self.clone();
+        signal::show_signal_dialog(&self.services_list, &names, move |whom, signal| {
+            let state = state.clone();
+            let services = services.clone();
+            glib::spawn_future_local(async move {
+                for service in services {
+                    let (name, scope) = (service.name.clone(), service.scope);
+                    let result = state
+                        .systemd
+                        .run(move |systemd| systemd.kill_unit(&name, whom, signal, scope))
+                        .await;
+                    match result {
+                        Ok(()) => state.show_toast(
+                            &format!("Sent {} to {}", signal.name(), service.name),
                             ToastPriority::Normal,
-                        );
+                        ),
+                        Err(e) => state.show_toast(
+                            &format!(
+                                "Failed to send {} to {}: {}",
+                                signal.name(),
+                                service.name,
+                                e
+                            ),
+                            ToastPriority::High,
+                        ),
                     }
                 }
-                ServiceAction::Stop => {
-                    if let Err(e) = self.systemd.stop_unit(service_name) {
-                        self.show_toast(
-                            &format!("Failed to stop {}: {}", service_name, e),
+            });
+        });
+    }
+
+    /// Clears the failed state of every unit of the given managers.
+    pub fn reset_all_failed(&self, scopes: &[UnitScope]) {
+        let scopes = scopes.to_vec();
+        let state = self.clone();
+        glib::spawn_future_local(async move {
+            for scope in scopes {
+                let result = state
+                    .systemd
+                    .run(move |systemd| systemd.reset_failed(scope))
+                    .await;
+                state.report_daemon_result(
+                    result,
+                    scope,
+                    "Reset failed units of the",
+                    "reset failed units of the",
+                );
+            }
+        });
+    }
+
+    /// Stops the services, but first asks for confirmation if active units
+    /// would be stopped along with them, or if that could not be determined.
+    fn confirm_stop(&self, services: Vec<ServiceData>) {
+        let state = self.clone();
+        glib::spawn_future_local(async move {
+            let targets: Vec<(String, UnitScope)> = services
+                .iter()
+                .map(|service| (service.name.clone(), service.scope))
+                .collect();
+            let (dependents, failures) = state
+                .systemd
+                .run(move |systemd| {
+                    let mut dependents = Vec::new();
+                    let mut failures = Vec::new();
+                    for (name, scope) in &targets {
+                        match systemd.active_dependents(name, *scope) {
+                            Ok(found) => dependents.extend(found),
+                            Err(e) => failures.push(format!("{}: {}", name, e)),
+                        }
+                    }
+                    (dependents, failures)
+                })
+                .await;
+            if dependents.is_empty() && failures.is_empty() {
+                state.run_service_action(ServiceAction::Stop, unit_keys(&services));
+                return;
+            }
+
+            let mut body = Vec::new();
+            if !dependents.is_empty() {
+                body.push(format!(
+                    "These active units depend on it and will be stopped as well:\n\n{}",
+                    dependents.join("\n")
+                ));
+            }
+            if !failures.is_empty() {
+                body.push(format!(
+                    "Could not determine which units depend on it, so more units may be stopped:\n\n{}",
+                    failures.join("\n")
+                ));
+            }
+            let names: Vec<&str> = services.iter().map(|s| s.name.as_str()).collect();
+            let dialog = adw::AlertDialog::builder()
+                .heading(format!("Stop {}?", names.join(", ")))
+                .body(body.join("\n\n"))
+                .default_response("cancel")
+                .close_response("cancel")
+                .build();
+            dialog.add_response("cancel", "Cancel");
+            dialog.add_response("stop", "Stop");
+            dialog.set_response_appearance("stop", adw::ResponseAppearance::Destructive);
+            let state_response = state.clone();
+            dialog.connect_response(Some("stop"), move |_, _| {
+                state_response.run_service_action(ServiceAction::Stop, unit_keys(&services));
+            });
+            dialog.present(Some(&state.services_list));
+        });
+    }
+
+    /// Asks for an instance of the selected template and starts or enables
+    /// it. The instance's row shows up through the live updates.
+    fn new_instance(&self, template: &ServiceData) {
+        if !templates::is_template(&template.name) {
+            self.show_toast(
+                &format!("{} is not a template unit", template.name),
+                ToastPriority::Normal,
+            );
+            return;
+        }
+        let state = self.clone();
+        let scope = template.scope;
+        instance::show_new_instance_dialog(
+            &self.services_list,
+            &template.name,
+            move |name, action| state.run_service_action(action, vec![(name, scope)]),
+        );
+    }
+
+    /// Opens the new service wizard. The unit file is installed and the
+    /// manager reloaded before the service is enabled and then started, so
+    /// its row arrives with the resync that follows the reload.
+    pub fn new_service(&self) {
+        let state = self.clone();
+        new_service::show_new_service_dialog(&self.services_list, move |request| {
+            let state = state.clone();
+            glib::spawn_future_local(async move {
+                let name = request.spec.unit_name();
+                let file = request.spec.to_unit_file();
+                let scope = request.scope;
+                let unit = name.clone();
+                let result = state
+                    .systemd
+                    .run(move |systemd| systemd.install_unit_file(&unit, scope, &file))
+                    .await;
+                let path = match result {
+                    Ok(path) => path,
+                    Err(e) => {
+                        state.show_toast(
+                            &format!("Failed to create {}: {}", name, e),
                             ToastPriority::High,
                         );
-                    } else {
-                        self.show_toast(
-                            &format!("Stopped {}", service_name),
-                            ToastPriority::Normal,
-                        );
+                        return;
                     }
+                };
+                state.show_toast(
+                    &format!("Created {}", path.display()),
+                    ToastPriority::Normal,
+                );
+                // A new unit is enabled for good and never forced, whatever
+                // the toggles say, and started only once it is enabled.
+                let options = UnitFileOptions {
+                    runtime: false,
+                    force: false,
+                };
+                let (enable, start) = (request.enable, request.start);
+                let unit = name.clone();
+                let (enabled, started) = state
+                    .systemd
+                    .run(move |systemd| {
+                        let enabled = enable.then(|| systemd.enable_unit(&unit, options, scope));
+                        let enable_failed = matches!(enabled, Some(Err(_)));
+                        let started =
+                            (start && !enable_failed).then(|| systemd.start_unit(&unit, scope));
+                        (enabled, started)
+                    })
+                    .await;
+                match enabled {
+                    Some(Ok(changes)) => {
+                        state.show_unit_file_changes(ServiceAction::Enable, &name, changes)
+                    }
+                    Some(Err(e)) if start => state.show_toast(
+                        &format!("Failed to enable {}, so it was not started: {}", name, e),
+                        ToastPriority::High,
+                    ),
+                    Some(Err(e)) => state.show_toast(
+                        &format!("Failed to enable {}: {}", name, e),
+                        ToastPriority::High,
+                    ),
+                    None => {}
                 }
-                ServiceAction::Enable => {
-                    if let Err(e) = self.systemd.enable_unit(service_name) {
-                        self.show_toast(
-                            &format!("Failed to enable {}: {}", service_name, e),
-                            ToastPriority::High,
-                        );
-                    } else {
-                        self.show_toast(
-                            &format!("Enabled {}", service_name),
-                            ToastPriority::Normal,
-                        );
+                match started {
+                    Some(Ok(result)) => {
+                        state.show_job_result(ServiceAction::Start, &name, scope, &result)
                     }
+                    Some(Err(e)) => state.show_toast(
+                        &format!("Failed to start {}: {}", name, e),
+                        ToastPriority::High,
+                    ),
+                    None => {}
                 }
-                ServiceAction::Disable => {
-                    if let Err(e) = self.systemd.disable_unit(service_name) {
-                        self.show_toast(
-                            &format!("Failed to disable {}: {}", service_name, e),
-                            ToastPriority::High,
-                        );
-                    } else {
-                        self.show_toast(
-                            &format!("Disabled {}", service_name),
-                            ToastPriority::Normal,
-                        );
+            });
+        });
+    }
+
+    /// Runs `action` on the units one after another. They need not be
+    /// listed; only Unmask looks at the row, to tell a runtime mask.
+    fn run_service_action(&self, action: ServiceAction, units: Vec<(String, UnitScope)>) {
+        let unit_file_options = UnitFileOptions {
+            runtime: self.runtime_toggle.is_active(),
+            force: true,
+        };
+        let reload_afterwards = self.daemon_reload_switch.is_active();
+        let state = self.clone();
+        glib::spawn_future_local(async move {
+            let mut reload_scopes = HashSet::new();
+            for (name, scope) in units {
+                let masked_runtime =
+                    state.enablement_of(&name, scope) == Some(EnablementStatus::MaskedRuntime);
+                state.set_busy(&name, scope, true);
+                let result = state
+                    .systemd
+                    .run({
+                        let name = name.clone();
+                        move |systemd| {
+                            perform_service_action(
+                                systemd,
+                                action,
+                                &name,
+                                scope,
+                                unit_file_options,
+                                masked_runtime,
+                            )
+                        }
+                    })
+                    .await;
+                state.set_busy(&name, scope, false);
+
+                match result {
+                    Err(e) => state.show_toast(
+                        &format!("Failed to {} {}: {}", action.verb(), name, e),
+                        ToastPriority::High,
+                    ),
+                    Ok(ActionOutcome::UnitFiles(changes)) => {
+                        reload_scopes.insert(scope);
+                        state.show_unit_file_changes(action, &name, changes);
+                    }
+                    Ok(ActionOutcome::Job(result)) => {
+                        state.show_job_result(action, &name, scope, &result)
                     }
+                    Ok(ActionOutcome::Done) => state.show_toast(
+                        &format!("{} {}", action.past_tense(), name),
+                        ToastPriority::Normal,
+                    ),
                 }
             }
+            if reload_afterwards && !reload_scopes.is_empty() {
+                state.reload_daemon(&reload_scopes.into_iter().collect::<Vec<_>>());
+            }
+        });
+    }
+
+    /// The enablement state of a listed unit.
+    fn enablement_of(&self, name: &str, scope: UnitScope) -> Option<EnablementStatus> {
+        self.service_widgets
+            .borrow()
+            .iter()
+            .find(|(data, _)| data.name == name && data.scope == scope)
+            .map(|(data, _)| data.enablement.clone())
+    }
+
+    /// Marks a row as having an operation in flight by showing a spinner
+    /// next to its name.
+    fn set_busy(&self, name: &str, scope: UnitScope, busy: bool) {
+        let key = (name.to_string(), scope);
+        if busy {
+            self.busy_services.borrow_mut().insert(key);
+        } else {
+            self.busy_services.borrow_mut().remove(&key);
+        }
+        if let Some((data, row)) = self
+            .service_widgets
+            .borrow()
+            .iter()
+            .find(|(data, _)| data.name == name && data.scope == scope)
+        {
+            row.set_child(Some(&create_service_row_content(data, busy)));
         }
-        self.refresh_services();
     }
 
-    pub fn show_toast(&self, message: &str, priority: ToastPriority) {
+    /// Reloads the managers of the given scopes. The list catches up through
+    /// the resync the manager's `Reloading` signal triggers.
+    pub fn reload_daemon(&self, scopes: &[UnitScope]) {
+        let scopes = scopes.to_vec();
+        let state = self.clone();
+        glib::spawn_future_local(async move {
+            for scope in scopes {
+                let result = state
+                    .systemd
+                    .run(move |systemd| systemd.daemon_reload(scope))
+                    .await;
+                state.report_daemon_result(result, scope, "Reloaded", "reload");
+            }
+        });
+    }
+
+    pub fn reexec_daemon(&self, scopes: &[UnitScope]) {
+        let scopes = scopes.to_vec();
+        let state = self.clone();
+        glib::spawn_future_local(async move {
+            for scope in scopes {
+                let result = state
+                    .systemd
+                    .run(move |systemd| systemd.daemon_reexec(scope))
+                    .await;
+                state.report_daemon_result(result, scope, "Re-executed", "re-execute");
+            }
+            state.refresh_services();
+        });
+    }
+
+    fn report_daemon_result(
+        &self,
+        result: crate::backend::Result<()>,
+        scope: UnitScope,
+        done: &str,
+        verb: &str,
+    ) {
+        match result {
+            Ok(()) => self.show_toast(
+                &format!("{} {} manager", done, format_scope(scope).to_lowercase()),
+                ToastPriority::Normal,
+            ),
+            Err(e) => self.show_toast(
+                &format!(
+                    "Failed to {} {} manager: {}",
+                    verb,
+                    format_scope(scope).to_lowercase(),
+                    e
+                ),
+                ToastPriority::High,
+            ),
+        }
+    }
+
+    /// Applies a live update from the backend to the affected rows only.
+    pub fn apply_service_event(&self, event: ServiceEvent) {
+        match event {
+            ServiceEvent::Changed(service) => self.update_service_row(&service),
+            ServiceEvent::Removed { name, scope } => {
+                self.remove_service_rows(|data| data.name == name && data.scope == scope)
+            }
+            ServiceEvent::Resync { scope, services } => {
+                for service in &services {
+                    self.update_service_row(service);
+                }
+                self.remove_service_rows(|data| {
+                    data.scope == scope
+                        && !services
+                            .iter()
+                            .any(|s| s.name == data.name && s.scope == data.scope)
+                });
+            }
+            ServiceEvent::JobsChanged => {}
+            ServiceEvent::WatchStopped { scope, reason } => self.show_toast(
+                &format!(
+                    "Live updates from the {} manager stopped: {}",
+                    format_scope(scope).to_lowercase(),
+                    reason
+                ),
+                ToastPriority::High,
+            ),
+        }
+    }
+
+    fn update_service_row(&self, service: &UnitInfo) {
+        let mut new_data = ServiceData::from(service);
+        let updated_row = {
+            let mut widgets = self.service_widgets.borrow_mut();
+            match widgets
+                .iter_mut()
+                .find(|(data, _)| data.name == service.name && data.scope == service.scope)
+            {
+                Some((data, row)) => {
+                    // Unloading drops the description; keep the last one seen.
+                    if new_data.description.is_empty() {
+                        new_data.description = data.description.clone();
+                    }
+                    if *data == new_data {
+                        return;
+                    }
+                    *data = new_data;
+                    let busy = self
+                        .busy_services
+                        .borrow()
+                        .contains(&(data.name.clone(), data.scope));
+                    row.set_child(Some(&create_service_row_content(data, busy)));
+                    row.clone()
+                }
+                None => {
+                    let (data, row) = create_service_entry(service);
+                    widgets.push((data, row.clone()));
+                    self.services_list.append(&row);
+                    row
+                }
+            }
+        };
+        if self.services_list.selected_row().as_ref() == Some(&updated_row) {
+            self.refresh_selected_properties();
+            self.update_action_sensitivity();
+        }
+    }
+
+    /// Drops matching rows. The widgets are taken out of `service_widgets`
+    /// first, since removing a selected row emits `row-selected`.
+    fn remove_service_rows(&self, predicate: impl Fn(&ServiceData) -> bool) {
+        let removed: Vec<ListBoxRow> = {
+            let mut widgets = self.service_widgets.borrow_mut();
+            let (removed, kept) = std::mem::take(&mut *widgets)
+                .into_iter()
+                .partition(|(data, _)| predicate(data));
+            *widgets = kept;
+            removed.into_iter().map(|(_, row)| row).collect()
+        };
+        for row in removed {
+            self.services_list.remove(&row);
+        }
+    }
+
+    /// Reports how a queued job ended. Anything short of success offers a
+    /// shortcut to the unit's journal.
+    pub fn show_job_result(
+        &self,
+        action: ServiceAction,
+        name: &str,
+        scope: UnitScope,
+        result: &JobResult,
+    ) {
+        if result.is_success() {
+            let suffix = match result {
+                JobResult::Skipped => " (nothing to do)",
+                _ => "",
+            };
+            self.show_toast(
+                &format!("{} {}{}", action.past_tense(), name, suffix),
+                ToastPriority::Normal,
+            );
+            return;
+        }
+
+        let (title, priority) = match result {
+            JobResult::Unfinished => (
+                format!(
+                    "Still waiting to {} {}, check its status later",
+                    action.verb(),
+                    name
+                ),
+                ToastPriority::Normal,
+            ),
+            _ => (
+                format!(
+                    "Failed to {} {}: {}",
+                    action.verb(),
+                    name,
+                    result.describe()
+                ),
+                ToastPriority::High,
+            ),
+        };
+        let toast = Toast::builder()
+            .title(title.as_str())
+            .priority(priority)
+            .timeout(8)
+            .button_label("View Logs")
+            .build();
+        let state = self.clone();
+        let name = name.to_string();
+        toast.connect_button_clicked(move |_| state.show_logs_for(&name, scope));
+        self.toast_overlay.add_toast(toast);
+    }
+
+    /// Selects the service's row and opens the journal tab for it.
+    pub fn show_logs_for(&self, name: &str, scope: UnitScope) {
+        if self.select_unit(name, scope) {
+            self.detail_pane.show_logs();
+        }
+    }
+
+    /// Selects the row of a unit, clearing the filters if they hide it.
+    /// Returns false if the unit is not listed at all.
+    pub fn select_unit(&self, name: &str, scope: UnitScope) -> bool {
+        let row = self
+            .service_widgets
+            .borrow()
+            .iter()
+            .find(|(data, _)| data.name == name && data.scope == scope)
+            .map(|(_, row)| row.clone());
+        let Some(row) = row else {
+            return false;
+        };
+        if !row.is_visible() {
+            for combo in [
+                &self.type_combo,
+                &self.status_combo,
+                &self.enablement_combo,
+                &self.scope_combo,
+            ] {
+                combo.set_active(Some(0));
+            }
+        }
+        self.services_list.select_row(Some(&row));
+        row.grab_focus();
+        true
+    }
+
+    /// Reports a unit file operation with a "Details" button listing the
+    /// symlinks systemd created or removed. Units without an `[Install]`
+    /// section get a high priority warning, since enabling them does nothing.
+    pub fn show_unit_file_changes(
+        &self,
+        action: ServiceAction,
+        service_name: &str,
+        changes: UnitFileChanges,
+    ) {
+        let (title, priority) = if changes.lacks_install_info() {
+            (
+                format!(
+                    "{} has no [Install] section, so {} had no effect",
+                    service_name,
+                    action.verb()
+                ),
+                ToastPriority::High,
+            )
+        } else if changes.changes.is_empty() {
+            (
+                format!("{} {} (nothing changed)", action.past_tense(), service_name),
+                ToastPriority::Normal,
+            )
+        } else {
+            (
+                format!("{} {}", action.past_tense(), service_name),
+                ToastPriority::Normal,
+            )
+        };
+
         let toast = Toast::builder()
-            .title(message)
+            .title(title.as_str())
             .priority(priority)
-            .timeout(3)
+            .timeout(5)
+            .button_label("Details")
             .build();
+        let overlay = self.toast_overlay.clone();
+        let heading = format!("{} {}", action.label(), service_name);
+        toast.connect_button_clicked(move |_| {
+            let dialog = adw::AlertDialog::builder()
+                .heading(heading.as_str())
+                .body(format_unit_file_changes(&changes))
+                .build();
+            dialog.add_response("close", "Close");
+            dialog.present(Some(&overlay));
+        });
         self.toast_overlay.add_toast(toast);
     }
+
+    pub fn show_toast(&self, message: &str, priority: ToastPriority) {
+        show_toast(&self.toast_overlay, message, priority);
+    }
+}
+
+/// Shows a plain toast. Errors stay up a little longer than confirmations.
+pub fn show_toast(toast_overlay: &ToastOverlay, message: &str, priority: ToastPriority) {
+    let timeout = match priority {
+        ToastPriority::High => 5,
+        _ => 3,
+    };
+    let toast = Toast::builder()
+        .title(message)
+        .priority(priority)
+        .timeout(timeout)
+        .build();
+    toast_overlay.add_toast(toast);
 }
 
 pub fn build_ui(app: &Application) {
@@ -185,23 +958,101 @@ pub fn build_ui(app: &Application) {
         .margin_start(12)
         .margin_end(12)
         .build();
+    // Sorted by name, with instances right below their template.
+    services_list.set_sort_func(|a, b| {
+        let key = |row: &ListBoxRow| {
+            let name = row.widget_name().to_string();
+            let group = templates::template_of(&name).unwrap_or_else(|| name.clone());
+            (group, !templates::is_template(&name), name)
+        };
+        key(a).cmp(&key(b)).into()
+    });
 
     let toast_overlay = ToastOverlay::new();
+    let jobs_panel = JobsPanel::new(systemd.clone(), toast_overlay.clone());
+    let timers_view = TimersView::new(systemd.clone(), toast_overlay.clone());
+    let failed_view = FailedView::new(systemd.clone(), toast_overlay.clone());
+    let boot_view = BootView::new(systemd.clone());
+    let detail_pane = Rc::new(DetailPane::new(systemd.clone(), toast_overlay.clone()));
     let state = Rc::new(RefCell::new(ServiceManagerState {
         systemd,
         service_widgets: Rc::new(RefCell::new(Vec::new())),
         services_list,
+        type_combo: ComboBoxText::new(),
         status_combo: ComboBoxText::new(),
         enablement_combo: ComboBoxText::new(),
+        scope_combo: ComboBoxText::new(),
         current_query: Rc::new(RefCell::new(String::new())),
         toast_overlay,
+        action_buttons: Vec::new(),
+        runtime_toggle: CheckButton::new(),
+        daemon_reload_switch: adw::SwitchRow::new(),
+        detail_pane,
+        busy_services: Rc::new(RefCell::new(HashSet::new())),
+        sensitivity_generation: Rc::new(Cell::new(0)),
+        jobs_panel,
+        timers_view,
+        failed_view,
+        boot_view,
     }));
 
+    let state_navigate = Rc::clone(&state);
+    state
+        .borrow()
+        .detail_pane
+        .connect_navigate(move |name, scope| {
+            let state = state_navigate.borrow();
+            if !state.select_unit(name, scope) {
+                state.show_toast(&format!("{} is not listed", name), ToastPriority::Normal);
+            }
+        });
+
     let sidebar = build_sidebar(Rc::clone(&state));
     let main_content = build_main_content(Rc::clone(&state));
     let window = create_window(app, Rc::clone(&state), sidebar, main_content);
 
+    let events = state.borrow().systemd.subscribe();
+    let state_events = Rc::clone(&state);
+    glib::timeout_add_local(Duration::from_millis(250), move || {
+        let pending: Vec<ServiceEvent> = events.try_iter().collect();
+        if !pending.is_empty() {
+            let state = state_events.borrow();
+            if pending
+                .iter()
+                .any(|event| matches!(event, ServiceEvent::JobsChanged))
+            {
+                state.jobs_panel.refresh();
+            }
+            if pending.iter().any(|event| match event {
+                ServiceEvent::Changed(unit) => unit.unit_type == UnitType::Timer,
+                ServiceEvent::Resync { .. } => true,
+                _ => false,
+            }) {
+                state.timers_view.refresh();
+            }
+            if pending.iter().any(|event| match event {
+                ServiceEvent::Changed(unit) => {
+                    unit.status == ServiceStatus::Failed
+                        || state.failed_view.lists(&unit.name, unit.scope)
+                }
+                ServiceEvent::Removed { name, scope } => state.failed_view.lists(name, *scope),
+                ServiceEvent::Resync { .. } => true,
+                _ => false,
+            }) {
+                state.failed_view.refresh();
+            }
+            for event in pending {
+                state.apply_service_event(event);
+            }
+            state.update_visibility();
+        }
+        glib::ControlFlow::Continue
+    });
+
     state.borrow().refresh_services();
+    state.borrow().jobs_panel.refresh();
+    state.borrow().timers_view.refresh();
+    state.borrow().failed_view.refresh();
     window.present();
 }
 
@@ -221,11 +1072,14 @@ fn build_sidebar(state: Rc<RefCell<ServiceManagerState>>) -> Box {
         .placeholder_text("Search names...")
         .build();
 
-    let (filter_controls, status_combo, enablement_combo) = create_filter_controls();
+    let (filter_controls, type_combo, status_combo, enablement_combo, scope_combo) =
+        create_filter_controls();
     {
         let mut state_borrow = state.borrow_mut();
+        state_borrow.type_combo = type_combo;
         state_borrow.status_combo = status_combo;
         state_borrow.enablement_combo = enablement_combo;
+        state_borrow.scope_combo = scope_combo;
     }
 
     let refresh_button = Button::builder().icon_name("view-refresh").build();
@@ -233,15 +1087,24 @@ fn build_sidebar(state: Rc<RefCell<ServiceManagerState>>) -> Box {
 
     let action_callback = {
         let state_clone = Rc::clone(&state);
-        move |action: ServiceAction| {
-            state_clone.borrow().handle_service_action(action);
+        move |action: PanelAction| {
+            state_clone.borrow().handle_panel_action(action);
         }
     };
 
     sidebar.append(&search_entry);
     sidebar.append(&Separator::new(Orientation::Horizontal));
     sidebar.append(&filter_controls);
-    sidebar.append(&create_service_actions(action_callback));
+    let (service_actions, action_buttons, runtime_toggle, daemon_reload_switch) =
+        create_service_actions(action_callback);
+    {
+        let mut state_borrow = state.borrow_mut();
+        state_borrow.action_buttons = action_buttons;
+        state_borrow.runtime_toggle = runtime_toggle;
+        state_borrow.daemon_reload_switch = daemon_reload_switch;
+    }
+    sidebar.append(&service_actions);
+    sidebar.append(&state.borrow().jobs_panel.container);
 
     let state_search = Rc::clone(&state);
     search_entry.connect_search_changed(move |search| {
@@ -250,6 +1113,11 @@ fn build_sidebar(state: Rc<RefCell<ServiceManagerState>>) -> Box {
         state_search.borrow().update_visibility();
     });
 
+    let state_type = Rc::clone(&state);
+    state.borrow().type_combo.connect_changed(move |_| {
+        state_type.borrow().update_visibility();
+    });
+
     let state_status = Rc::clone(&state);
     state.borrow().status_combo.connect_changed(move |_| {
         state_status.borrow().update_visibility();
@@ -260,12 +1128,27 @@ fn build_sidebar(state: Rc<RefCell<ServiceManagerState>>) -> Box {
         state_enablement.borrow().update_visibility();
     });
 
+    let state_selection = Rc::clone(&state);
+    state
+        .borrow()
+        .services_list
+        .connect_row_selected(move |_, _| {
+            let state = state_selection.borrow();
+            state.update_action_sensitivity();
+            state.show_selected_details();
+        });
+
+    let state_scope = Rc::clone(&state);
+    state.borrow().scope_combo.connect_changed(move |_| {
+        state_scope.borrow().update_visibility();
+    });
+
     sidebar
 }
 
 fn build_main_content(state: Rc<RefCell<ServiceManagerState>>) -> Box {
     let services_container = Box::builder()
-        .orientation(Orientation::Vertical)
+        .orientation(Orientation::Horizontal)
         .hexpand(true)
         .vexpand(true)
         .build();
@@ -279,6 +1162,8 @@ fn build_main_content(state: Rc<RefCell<ServiceManagerState>>) -> Box {
         .build();
 
     services_container.append(&services_scroll);
+    services_container.append(&Separator::new(Orientation::Vertical));
+    services_container.append(&state.borrow().detail_pane.container);
     services_container
 }
 
@@ -311,9 +1196,48 @@ fn create_window(
     main_box.append(&Separator::new(Orientation::Vertical));
     main_box.append(&main_content);
 
-    state.borrow().toast_overlay.set_child(Some(&main_box));
-    let header = HeaderBar::new();
+    let view_stack = adw::ViewStack::new();
+    view_stack.add_titled_with_icon(&main_box, Some("units"), "Units", "view-list-symbolic");
+    view_stack.add_titled_with_icon(
+        &state.borrow().timers_view.container,
+        Some("timers"),
+        "Timers",
+        "alarm-symbolic",
+    );
+    view_stack.add_titled_with_icon(
+        &state.borrow().failed_view.container,
+        Some("failed"),
+        "Failed",
+        "dialog-warning-symbolic",
+    );
+    let state_logs = Rc::clone(&state);
+    let stack_logs = view_stack.clone();
+    state
+        .borrow()
+        .failed_view
+        .connect_show_logs(move |name, scope| {
+            stack_logs.set_visible_child_name("units");
+            state_logs.borrow().show_logs_for(name, scope);
+        });
+    view_stack.add_titled_with_icon(
+        &state.borrow().boot_view.container,
+        Some("boot"),
+        "Boot",
+        "system-reboot-symbolic",
+    );
+
+    state.borrow().toast_overlay.set_child(Some(&view_stack));
+    let header = HeaderBar::builder()
+        .title_widget(
+            &adw::ViewSwitcher::builder()
+                .stack(&view_stack)
+                .policy(adw::ViewSwitcherPolicy::Wide)
+                .build(),
+        )
+        .build();
     header.pack_start(&Button::builder().icon_name("view-refresh").build());
+    header.pack_start(&create_new_service_button(Rc::clone(&state)));
+    header.pack_end(&create_daemon_reload_button(Rc::clone(&state)));
 
     let vbox = Box::new(Orientation::Vertical, 0);
     vbox.append(&header);
@@ -321,57 +1245,192 @@ fn create_window(
 
     Window::builder()
         .application(app)
-        .default_width(1200)
+        .default_width(1600)
         .default_height(800)
         .title("Service Manager")
         .content(&vbox)
         .build()
 }
 
+/// What a finished action reports back: the result of the queued job, the
+/// changes of a unit file operation, or nothing for immediate calls.
+enum ActionOutcome {
+    Job(JobResult),
+    UnitFiles(UnitFileChanges),
+    Done,
+}
+
+/// Runs one action for one unit. Called on a worker thread.
+fn perform_service_action(
+    systemd: &SystemdServiceManager,
+    action: ServiceAction,
+    name: &str,
+    scope: UnitScope,
+    unit_file_options: UnitFileOptions,
+    masked_runtime: bool,
+) -> crate::backend::Result<ActionOutcome> {
+    match action {
+        ServiceAction::Start => systemd.start_unit(name, scope).map(ActionOutcome::Job),
+        ServiceAction::Stop => systemd.stop_unit(name, scope).map(ActionOutcome::Job),
+        ServiceAction::Restart => systemd.restart_unit(name, scope).map(ActionOutcome::Job),
+        ServiceAction::Reload => systemd.reload_unit(name, scope).map(ActionOutcome::Job),
+        ServiceAction::TryRestart => systemd
+            .try_restart_unit(name, scope)
+            .map(ActionOutcome::Job),
+        ServiceAction::ReloadOrRestart => systemd
+            .reload_or_restart_unit(name, scope)
+            .map(ActionOutcome::Job),
+        ServiceAction::Enable => systemd
+            .enable_unit(name, unit_file_options, scope)
+            .map(ActionOutcome::UnitFiles),
+        ServiceAction::Disable => systemd
+            .disable_unit(name, unit_file_options, scope)
+            .map(ActionOutcome::UnitFiles),
+        ServiceAction::Mask => systemd
+            .mask_unit(name, unit_file_options.runtime, scope)
+            .map(ActionOutcome::UnitFiles),
+        ServiceAction::Unmask => systemd
+            .unmask_unit(name, masked_runtime, scope)
+            .map(ActionOutcome::UnitFiles),
+        ServiceAction::ResetFailed => systemd
+            .reset_failed_unit(name, scope)
+            .map(|()| ActionOutcome::Done),
+    }
+}
+
+fn create_new_service_button(state: Rc<RefCell<ServiceManagerState>>) -> Button {
+    let button = Button::builder()
+        .icon_name("list-add-symbolic")
+        .tooltip_text("New service")
+        .build();
+    button.connect_clicked(move |_| state.borrow().new_service());
+    button
+}
+
+/// Header button that reloads both the system and the user manager, with a
+/// dropdown offering the heavier re-execute.
+fn create_daemon_reload_button(state: Rc<RefCell<ServiceManagerState>>) -> adw::SplitButton {
+    let reexec_button = Button::builder()
+        .label("Re-execute systemd")
+        .css_classes(["flat"])
+        .build();
+    let reset_failed_button = Button::builder()
+        .label("Reset failed units")
+        .css_classes(["flat"])
+        .build();
+    let popover_box = Box::new(Orientation::Vertical, 0);
+    popover_box.append(&reexec_button);
+    popover_box.append(&reset_failed_button);
+    let popover = gtk4::Popover::builder().child(&popover_box).build();
+
+    let button = adw::SplitButton::builder()
+        .icon_name("emblem-synchronizing-symbolic")
+        .tooltip_text("Reload systemd configuration")
+        .dropdown_tooltip("More daemon actions")
+        .popover(&popover)
+        .build();
+
+    let state_reload = Rc::clone(&state);
+    button.connect_clicked(move |_| {
+        state_reload
+            .borrow()
+            .reload_daemon(&[UnitScope::System, UnitScope::User]);
+    });
+
+    let (state_reexec, popover_reexec) = (Rc::clone(&state), popover.clone());
+    reexec_button.connect_clicked(move |_| {
+        popover_reexec.popdown();
+        state_reexec
+            .borrow()
+            .reexec_daemon(&[UnitScope::System, UnitScope::User]);
+    });
+
+    reset_failed_button.connect_clicked(move |_| {
+        popover.popdown();
+        state
+            .borrow()
+            .reset_all_failed(&[UnitScope::System, UnitScope::User]);
+    });
+
+    button
+}
+
 fn setup_refresh_button(button: Button, state: Rc<RefCell<ServiceManagerState>>) {
     button.connect_clicked(move |_| {
         state.borrow().refresh_services();
     });
 }
 
-fn get_selected_services(list_box: &ListBox) -> Vec<String> {
+/// The name and scope of each service, which is all
+/// [`ServiceManagerState::run_service_action`] needs.
+fn unit_keys(services: &[ServiceData]) -> Vec<(String, UnitScope)> {
+    services
+        .iter()
+        .map(|service| (service.name.clone(), service.scope))
+        .collect()
+}
+
+fn get_selected_services(
+    list_box: &ListBox,
+    service_widgets: &[(ServiceData, ListBoxRow)],
+) -> Vec<ServiceData> {
     list_box
         .selected_rows()
         .iter()
         .filter_map(|row| {
-            let name = row.widget_name();
-            if !name.is_empty() {
-                Some(name.to_string())
-            } else {
-                None
-            }
+            service_widgets
+                .iter()
+                .find(|(_, widget)| widget == row)
+                .map(|(data, _)| data.clone())
         })
         .collect()
 }
 
-pub fn create_service_entry(service: &ServiceInfo) -> (ServiceData, ListBoxRow) {
-    let service_data = ServiceData {
-        name: service.name.clone(),
-        status: service.status.clone(),
-        enablement: service.enablement_status.clone(),
-    };
+pub fn create_service_entry(service: &UnitInfo) -> (ServiceData, ListBoxRow) {
+    let service_data = ServiceData::from(service);
+
+    let row = ListBoxRow::builder()
+        .name(&service_data.name)
+        .child(&create_service_row_content(&service_data, false))
+        .build();
+
+    (service_data, row)
+}
 
+fn create_service_row_content(service: &ServiceData, busy: bool) -> Box {
+    let is_instance = templates::template_of(&service.name).is_some();
     let row_box = Box::builder()
         .orientation(Orientation::Vertical)
         .spacing(6)
-        .margin_start(12)
+        .margin_start(if is_instance { 36 } else { 12 })
         .margin_end(12)
         .margin_top(9)
         .margin_bottom(9)
         .build();
 
-    row_box.append(
+    let title_box = Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(6)
+        .build();
+    title_box.append(
         &Label::builder()
             .label(&service.name)
             .halign(Align::Start)
             .css_classes(["heading"])
             .build(),
     );
+    if templates::is_template(&service.name) {
+        title_box.append(
+            &Label::builder()
+                .label("Template")
+                .css_classes(["caption", "accent"])
+                .build(),
+        );
+    }
+    if busy {
+        title_box.append(&Spinner::builder().spinning(true).build());
+    }
+    row_box.append(&title_box);
 
     row_box.append(
         &Label::builder()
@@ -392,7 +1451,23 @@ pub fn create_service_entry(service: &ServiceInfo) -> (ServiceData, ListBoxRow)
 
     info_box.append(
         &Label::builder()
-            .label(&format!("Status: {}", format_status(&service.status)))
+            .label(format!("Type: {}", format_unit_type(service.unit_type)))
+            .halign(Align::Start)
+            .css_classes(["dim-label"])
+            .build(),
+    );
+
+    info_box.append(
+        &Label::builder()
+            .label(if service.sub_state.is_empty() {
+                format!("Status: {}", format_status(&service.status))
+            } else {
+                format!(
+                    "Status: {} ({})",
+                    format_status(&service.status),
+                    service.sub_state
+                )
+            })
             .halign(Align::Start)
             .css_classes(get_status_css_classes(&service.status))
             .build(),
@@ -400,26 +1475,60 @@ pub fn create_service_entry(service: &ServiceInfo) -> (ServiceData, ListBoxRow)
 
     info_box.append(
         &Label::builder()
-            .label(&format!(
+            .label(format!(
                 "Enablement: {}",
-                format_enablement(&service.enablement_status)
+                format_enablement(&service.enablement)
             ))
             .halign(Align::Start)
-            .css_classes(get_enablement_css_classes(&service.enablement_status))
+            .css_classes(get_enablement_css_classes(&service.enablement))
             .build(),
     );
 
-    row_box.append(&info_box);
+    info_box.append(
+        &Label::builder()
+            .label(format!("Scope: {}", format_scope(service.scope)))
+            .halign(Align::Start)
+            .css_classes(["dim-label"])
+            .build(),
+    );
 
-    let row = ListBoxRow::builder()
-        .name(&service_data.name)
-        .child(&row_box)
-        .build();
+    if !service.load_state.is_empty() && service.load_state != "loaded" {
+        info_box.append(
+            &Label::builder()
+                .label(format!("Load: {}", service.load_state))
+                .halign(Align::Start)
+                .css_classes(["warning"])
+                .build(),
+        );
+    }
 
-    (service_data, row)
+    if let Some((job_id, job_type)) = &service.job {
+        info_box.append(
+            &Label::builder()
+                .label(format!("Job #{}: {}", job_id, job_type))
+                .halign(Align::Start)
+                .css_classes(["accent"])
+                .build(),
+        );
+    }
+
+    row_box.append(&info_box);
+
+    if let Some(type_info) = &service.type_info {
+        row_box.append(
+            &Label::builder()
+                .label(format_type_info(type_info))
+                .halign(Align::Start)
+                .wrap(true)
+                .justify(Justification::Left)
+                .css_classes(["caption", "dim-label"])
+                .build(),
+        );
+    }
+    row_box
 }
 
-pub fn create_filter_controls() -> (Box, ComboBoxText, ComboBoxText) {
+pub fn create_filter_controls() -> (Box, ComboBoxText, ComboBoxText, ComboBoxText, ComboBoxText) {
     let main_box = Box::builder()
         .orientation(Orientation::Vertical)
         .margin_top(12)
@@ -429,10 +1538,17 @@ pub fn create_filter_controls() -> (Box, ComboBoxText, ComboBoxText) {
         .build();
 
     let group = adw::PreferencesGroup::builder()
-        .title("Service Filters")
-        .description("Filter services by status and enablement state")
+        .title("Unit Filters")
+        .description("Filter units by type, status, enablement state and scope")
         .build();
 
+    let type_options: Vec<&str> = std::iter::once("All")
+        .chain(UnitType::ALL.into_iter().map(format_unit_type))
+        .collect();
+    let (type_row, type_combo) = create_combo_row("Type", &type_options);
+    // Devices, slices and scopes would bury everything else by default.
+    type_combo.set_active(Some(1));
+
     let (status_row, status_combo) = create_combo_row(
         "Status",
         &[
@@ -442,6 +1558,7 @@ pub fn create_filter_controls() -> (Box, ComboBoxText, ComboBoxText) {
             "Failed",
             "Activating",
             "Deactivating",
+            "Not Loaded",
             "Unknown",
         ],
     );
@@ -456,15 +1573,27 @@ pub fn create_filter_controls() -> (Box, ComboBoxText, ComboBoxText) {
             "Indirect",
             "Generated",
             "Transient",
+            "Masked",
+            "Masked (Runtime)",
             "Unknown",
         ],
     );
 
+    let (scope_row, scope_combo) = create_combo_row("Scope", &["All", "System", "User"]);
+
+    group.add(&type_row);
     group.add(&status_row);
     group.add(&enablement_row);
+    group.add(&scope_row);
     main_box.append(&group);
 
-    (main_box, status_combo, enablement_combo)
+    (
+        main_box,
+        type_combo,
+        status_combo,
+        enablement_combo,
+        scope_combo,
+    )
 }
 
 fn create_combo_row(title: &str, options: &[&str]) -> (adw::ActionRow, ComboBoxText) {
@@ -487,7 +1616,9 @@ fn create_combo_row(title: &str, options: &[&str]) -> (adw::ActionRow, ComboBoxT
     (row, combo)
 }
 
-pub fn create_service_actions<F: Fn(ServiceAction) + 'static + Clone>(button_callback: F) -> Box {
+pub fn create_service_actions<F: Fn(PanelAction) + 'static + Clone>(
+    button_callback: F,
+) -> (Box, Vec<(PanelAction, Button)>, CheckButton, adw::SwitchRow) {
     let main_box = Box::builder()
         .orientation(Orientation::Vertical)
         .margin_top(12)
@@ -501,16 +1632,16 @@ pub fn create_service_actions<F: Fn(ServiceAction) + 'static + Clone>(button_cal
         .description("Perform actions on selected services")
         .build();
 
-    let state_row = create_action_buttons(
+    let (state_row, mut buttons) = create_action_buttons(
         "State",
         &[
             (
-                &ServiceAction::Start,
+                PanelAction::Service(ServiceAction::Start),
                 "Start service",
                 "media-playback-start-symbolic",
             ),
             (
-                &ServiceAction::Stop,
+                PanelAction::Service(ServiceAction::Stop),
                 "Stop service",
                 "media-playback-stop-symbolic",
             ),
@@ -518,16 +1649,50 @@ pub fn create_service_actions<F: Fn(ServiceAction) + 'static + Clone>(button_cal
         &button_callback,
     );
 
-    let enablement_row = create_action_buttons(
+    let (restart_row, restart_buttons) = create_action_buttons(
+        "Restart",
+        &[
+            (
+                PanelAction::Service(ServiceAction::Restart),
+                "Restart service",
+                "view-refresh-symbolic",
+            ),
+            (
+                PanelAction::Service(ServiceAction::TryRestart),
+                "Restart service only if it is running",
+                "view-refresh-symbolic",
+            ),
+        ],
+        &button_callback,
+    );
+
+    let (reload_row, reload_buttons) = create_action_buttons(
+        "Reload",
+        &[
+            (
+                PanelAction::Service(ServiceAction::Reload),
+                "Reload service configuration",
+                "document-revert-symbolic",
+            ),
+            (
+                PanelAction::Service(ServiceAction::ReloadOrRestart),
+                "Reload if supported, otherwise restart",
+                "document-revert-symbolic",
+            ),
+        ],
+        &button_callback,
+    );
+
+    let (enablement_row, enablement_buttons) = create_action_buttons(
         "Enablement",
         &[
             (
-                &ServiceAction::Enable,
+                PanelAction::Service(ServiceAction::Enable),
                 "Enable auto-start",
                 "system-run-symbolic",
             ),
             (
-                &ServiceAction::Disable,
+                PanelAction::Service(ServiceAction::Disable),
                 "Disable auto-start",
                 "window-close-symbolic",
             ),
@@ -535,18 +1700,117 @@ pub fn create_service_actions<F: Fn(ServiceAction) + 'static + Clone>(button_cal
         &button_callback,
     );
 
+    let runtime_toggle = CheckButton::builder()
+        .label("Runtime only")
+        .tooltip_text("Enable, disable or mask until the next reboot only")
+        .valign(Align::Center)
+        .build();
+    enablement_row.add_suffix(&runtime_toggle);
+
+    let (masking_row, masking_buttons) = create_action_buttons(
+        "Masking",
+        &[
+            (
+                PanelAction::Service(ServiceAction::Mask),
+                "Prevent the service from being started",
+                "action-unavailable-symbolic",
+            ),
+            (
+                PanelAction::Service(ServiceAction::Unmask),
+                "Allow the service to be started again",
+                "object-select-symbolic",
+            ),
+        ],
+        &button_callback,
+    );
+
+    let (template_row, template_buttons) = create_action_buttons(
+        "Template",
+        &[(
+            PanelAction::NewInstance,
+            "Start or enable an instance of the selected template",
+            "list-add-symbolic",
+        )],
+        &button_callback,
+    );
+
     group.add(&state_row);
+    group.add(&restart_row);
+    group.add(&reload_row);
     group.add(&enablement_row);
+    let daemon_reload_switch = adw::SwitchRow::builder()
+        .title("Reload systemd afterwards")
+        .subtitle("Run a daemon reload after unit file changes")
+        .build();
+
+    let (more_row, more_buttons) = create_more_actions_row(&button_callback);
+
+    group.add(&masking_row);
+    group.add(&template_row);
+    group.add(&more_row);
+    group.add(&daemon_reload_switch);
     main_box.append(&group);
 
-    main_box
+    buttons.extend(restart_buttons);
+    buttons.extend(reload_buttons);
+    buttons.extend(enablement_buttons);
+    buttons.extend(masking_buttons);
+    buttons.extend(template_buttons);
+    buttons.extend(more_buttons);
+    (main_box, buttons, runtime_toggle, daemon_reload_switch)
+}
+
+/// Row with a menu for the rarely used actions, so they don't crowd the
+/// button rows.
+fn create_more_actions_row<F: Fn(PanelAction) + 'static + Clone>(
+    callback: &F,
+) -> (adw::ActionRow, Vec<(PanelAction, Button)>) {
+    let menu_box = Box::new(Orientation::Vertical, 0);
+    let popover = gtk4::Popover::builder().child(&menu_box).build();
+    let mut buttons = Vec::new();
+    for (action, tooltip) in [
+        (
+            PanelAction::Kill,
+            "Send a signal such as SIGHUP or SIGKILL to the service's processes",
+        ),
+        (
+            PanelAction::Service(ServiceAction::ResetFailed),
+            "Clear the failed state and restart counter",
+        ),
+    ] {
+        let button = Button::builder()
+            .label(action.label())
+            .tooltip_text(tooltip)
+            .css_classes(["flat"])
+            .build();
+        let (callback, popover) = (callback.clone(), popover.clone());
+        button.connect_clicked(move |_| {
+            popover.popdown();
+            callback(action);
+        });
+        menu_box.append(&button);
+        buttons.push((action, button));
+    }
+
+    let menu_button = gtk4::MenuButton::builder()
+        .icon_name("view-more-symbolic")
+        .tooltip_text("More actions")
+        .popover(&popover)
+        .valign(Align::Center)
+        .build();
+    let row = adw::ActionRow::builder()
+        .title("More")
+        .activatable(false)
+        .build();
+    row.add_suffix(&menu_button);
+    (row, buttons)
 }
 
-fn create_action_buttons<F: Fn(ServiceAction) + 'static + Clone>(
+fn create_action_buttons<F: Fn(PanelAction) + 'static + Clone>(
     title: &str,
-    actions: &[(&ServiceAction, &str, &str)],
+    actions: &[(PanelAction, &str, &str)],
     callback: &F,
-) -> adw::ActionRow {
+) -> (adw::ActionRow, Vec<(PanelAction, Button)>) {
     let button_box = Box::builder()
         .css_classes(["linked"])
         .orientation(Orientation::Horizontal)
@@ -555,6 +1819,7 @@ fn create_action_buttons<F: Fn(ServiceAction) + 'static + Clone>(
         .margin_bottom(6)
         .build();
 
+    let mut buttons = Vec::new();
     for (action, tooltip, icon) in actions {
         let button = Button::builder()
             .icon_name(*icon)
@@ -563,9 +1828,10 @@ fn create_action_buttons<F: Fn(ServiceAction) + 'static + Clone>(
             .build();
 
         let callback_clone = callback.clone();
-        let action_clone = action.to_owned().to_owned();
+        let action_clone = *action;
         button.connect_clicked(move |_| callback_clone(action_clone));
         button_box.append(&button);
+        buttons.push((*action, button));
     }
 
     let row = adw::ActionRow::builder()
@@ -574,18 +1840,25 @@ fn create_action_buttons<F: Fn(ServiceAction) + 'static + Clone>(
         .build();
 
     row.add_suffix(&button_box);
-    row
+    (row, buttons)
 }
 
 pub fn update_service_visibility(
     service_widgets: &[(ServiceData, ListBoxRow)],
     query: &str,
+    type_filter: &str,
     status_filter: &str,
     enablement_filter: &str,
+    scope_filter: &str,
 ) {
     for (service_data, row) in service_widgets {
         let visible = service_data.matches_query(query)
-            && service_data.matches_filters(status_filter, enablement_filter);
+            && service_data.matches_filters(
+                type_filter,
+                status_filter,
+                enablement_filter,
+                scope_filter,
+            );
         row.set_visible(visible);
     }
 }
@@ -597,6 +1870,7 @@ pub fn format_status(status: &ServiceStatus) -> &'static str {
         ServiceStatus::Failed => "Failed",
         ServiceStatus::Activating => "Activating",
         ServiceStatus::Deactivating => "Deactivating",
+        ServiceStatus::NotLoaded => "Not Loaded",
         ServiceStatus::Unknown(_) => "Unknown",
     }
 }
@@ -609,10 +1883,102 @@ pub fn format_enablement(enablement: &EnablementStatus) -> &'static str {
         EnablementStatus::Indirect => "Indirect",
         EnablementStatus::Generated => "Generated",
         EnablementStatus::Transient => "Transient",
+        EnablementStatus::Masked => "Masked",
+        EnablementStatus::MaskedRuntime => "Masked (Runtime)",
         EnablementStatus::Unknown(_) => "Unknown",
     }
 }
 
+/// Renders the reply of a unit file operation for the details dialog.
+fn format_unit_file_changes(changes: &UnitFileChanges) -> String {
+    let mut lines: Vec<String> = changes.changes.iter().map(ToString::to_string).collect();
+    if lines.is_empty() {
+        lines.push("No files were changed.".to_string());
+    }
+    if changes.lacks_install_info() {
+        lines.push(String::new());
+        lines.push(
+            "The unit files have no installation config (WantedBy=, RequiredBy=, Also=, \
+             Alias= settings in the [Install] section, and DefaultInstance= for template \
+             units), so there is nothing to enable."
+                .to_string(),
+        );
+    }
+    lines.join("\n")
+}
+
+pub fn format_unit_type(unit_type: UnitType) -> &'static str {
+    match unit_type {
+        UnitType::Service => "Service",
+        UnitType::Socket => "Socket",
+        UnitType::Target => "Target",
+        UnitType::Timer => "Timer",
+        UnitType::Mount => "Mount",
+        UnitType::Automount => "Automount",
+        UnitType::Path => "Path",
+        UnitType::Slice => "Slice",
+        UnitType::Scope => "Scope",
+        UnitType::Device => "Device",
+        UnitType::Swap => "Swap",
+    }
+}
+
+/// One-line summary of the type-specific state, e.g. when a timer fires next
+/// or where a socket listens.
+pub fn format_type_info(type_info: &UnitTypeInfo) -> String {
+    let join = |pairs: &[(String, String)]| {
+        pairs
+            .iter()
+            .map(|(kind, value)| format!("{} ({})", value, kind))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    match type_info {
+        UnitTypeInfo::Timer {
+            next_elapse_realtime,
+            next_elapse_monotonic,
+            last_trigger,
+            unit,
+        } => format!(
+            "Next: {} · Last: {} · Activates: {}",
+            format_next_elapse(*next_elapse_realtime, *next_elapse_monotonic),
+            detail::format_timestamp(*last_trigger),
+            unit
+        ),
+        UnitTypeInfo::Socket { listen } => format!("Listen: {}", join(listen)),
+        UnitTypeInfo::Mount { what } => format!("What: {}", what),
+        UnitTypeInfo::Path { paths } => format!("Watches: {}", join(paths)),
+    }
+}
+
+/// Timers report either a wall clock or a monotonic elapse time, whichever
+/// comes first; the monotonic one is converted using the current offset
+/// between the two clocks. systemd uses `u64::MAX` for "no elapse".
+pub fn next_elapse(realtime: u64, monotonic: u64) -> Option<u64> {
+    let valid = |usec: &u64| *usec != 0 && *usec != u64::MAX;
+    let offset = glib::real_time() - glib::monotonic_time();
+    let from_monotonic = Some(monotonic)
+        .filter(valid)
+        .map(|usec| (usec as i64 + offset).max(0) as u64);
+    match (Some(realtime).filter(valid), from_monotonic) {
+        (Some(realtime), Some(monotonic)) => Some(realtime.min(monotonic)),
+        (realtime, monotonic) => realtime.or(monotonic),
+    }
+}
+
+fn format_next_elapse(realtime: u64, monotonic: u64) -> String {
+    next_elapse(realtime, monotonic)
+        .map(detail::format_timestamp)
+        .unwrap_or_else(|| "n/a".to_string())
+}
+
+pub fn format_scope(scope: UnitScope) -> &'static str {
+    match scope {
+        UnitScope::System => "System",
+        UnitScope::User => "User",
+    }
+}
+
 pub fn get_status_css_classes(status: &ServiceStatus) -> &'static [&'static str] {
     match status {
         ServiceStatus::Active => &["success"],
@@ -627,6 +1993,7 @@ pub fn get_enablement_css_classes(enablement: &EnablementStatus) -> &'static [&'
         EnablementStatus::Enabled => &["success"],
         EnablementStatus::Disabled => &["dim-label"],
         EnablementStatus::Static => &["warning"],
+        EnablementStatus::Masked | EnablementStatus::MaskedRuntime => &["error"],
         _ => &["dim-label"],
     }
 }
@@ -0,0 +1,246 @@
+use crate::backend::boot::{BootAnalysis, UnitTiming};
+use crate::backend::{SystemdServiceManager, UnitScope};
+use adw::prelude::*;
+use gtk4::{Align, Box, Button, Label, Orientation, ToggleButton, glib};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlameOrder {
+    Duration,
+    Name,
+    Start,
+}
+
+/// Page with the boot phases, the critical chain of the default target and
+/// the per-unit activation times of the system manager. Read the first time
+/// the page is shown and on refresh.
+pub struct BootView {
+    pub container: adw::PreferencesPage,
+    times_group: adw::PreferencesGroup,
+    chain_group: adw::PreferencesGroup,
+    blame_group: adw::PreferencesGroup,
+    rows: RefCell<Vec<(adw::PreferencesGroup, adw::ActionRow)>>,
+    analysis: RefCell<Option<BootAnalysis>>,
+    order: Cell<BlameOrder>,
+    loaded: Cell<bool>,
+    systemd: SystemdServiceManager,
+}
+
+impl BootView {
+    pub fn new(systemd: SystemdServiceManager) -> Rc<Self> {
+        let refresh_button = Button::builder()
+            .icon_name("view-refresh-symbolic")
+            .tooltip_text("Re-read boot timestamps")
+            .valign(Align::Center)
+            .css_classes(["flat"])
+            .build();
+        let times_group = adw::PreferencesGroup::builder()
+            .title("Startup")
+            .description("Time spent in each boot phase")
+            .header_suffix(&refresh_button)
+            .build();
+        let chain_group = adw::PreferencesGroup::builder()
+            .title("Critical Chain")
+            .description("Units the default target waited for, @ when active, + activation time")
+            .build();
+
+        let order_box = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .css_classes(["linked"])
+            .valign(Align::Center)
+            .build();
+        let order_buttons = [
+            (BlameOrder::Duration, "Slowest"),
+            (BlameOrder::Start, "Start"),
+            (BlameOrder::Name, "Name"),
+        ]
+        .map(|(order, label)| {
+            let button = ToggleButton::builder()
+                .label(label)
+                .active(order == BlameOrder::Duration)
+                .build();
+            order_box.append(&button);
+            (order, button)
+        });
+        for (_, button) in &order_buttons[1..] {
+            button.set_group(Some(&order_buttons[0].1));
+        }
+        let blame_group = adw::PreferencesGroup::builder()
+            .title("Blame")
+            .description("Activation time of every unit started during boot")
+            .header_suffix(&order_box)
+            .build();
+
+        let container = adw::PreferencesPage::new();
+        container.add(&times_group);
+        container.add(&chain_group);
+        container.add(&blame_group);
+
+        let view = Rc::new(Self {
+            container,
+            times_group,
+            chain_group,
+            blame_group,
+            rows: RefCell::new(Vec::new()),
+            analysis: RefCell::new(None),
+            order: Cell::new(BlameOrder::Duration),
+            loaded: Cell::new(false),
+            systemd,
+        });
+
+        let view_map = Rc::clone(&view);
+        view.container.connect_map(move |_| {
+            if !view_map.loaded.get() {
+                view_map.refresh();
+            }
+        });
+
+        let view_refresh = Rc::clone(&view);
+        refresh_button.connect_clicked(move |_| view_refresh.refresh());
+
+        for (order, button) in order_buttons {
+            let view_order = Rc::clone(&view);
+            button.connect_toggled(move |button| {
+                if button.is_active() {
+                    view_order.order.set(order);
+                    view_order.show_analysis();
+                }
+            });
+        }
+
+        view
+    }
+
+    pub fn refresh(self: &Rc<Self>) {
+        self.loaded.set(true);
+        let view = Rc::clone(self);
+        glib::spawn_future_local(async move {
+            let analysis = view
+                .systemd
+                .run(|systemd| systemd.analyze_boot(UnitScope::System))
+                .await;
+            match analysis {
+                Ok(analysis) => {
+                    *view.analysis.borrow_mut() = Some(analysis);
+                    view.show_analysis();
+                }
+                Err(e) => {
+                    view.clear();
+                    view.add_row(
+                        &view.times_group,
+                        "Could not read boot timestamps",
+                        &e.to_string(),
+                    );
+                }
+            }
+        });
+    }
+
+    fn clear(&self) {
+        for (group, row) in self.rows.borrow_mut().drain(..) {
+            group.remove(&row);
+        }
+    }
+
+    fn show_analysis(&self) {
+        self.clear();
+        let analysis = self.analysis.borrow();
+        let Some(analysis) = analysis.as_ref() else {
+            return;
+        };
+
+        let times = &analysis.times;
+        for (phase, duration) in [
+            ("Firmware", times.firmware),
+            ("Boot Loader", times.loader),
+            ("Kernel", times.kernel),
+            ("Initrd", times.initrd),
+            ("Userspace", times.userspace),
+        ] {
+            if let Some(duration) = duration {
+                self.add_row(&self.times_group, phase, &format_duration(duration));
+            }
+        }
+        let total = match times.userspace {
+            Some(_) => format_duration(times.total()),
+            None => "Boot is still in progress".to_string(),
+        };
+        self.add_row(&self.times_group, "Total", &total);
+
+        if analysis.critical_chain.is_empty() {
+            self.add_row(
+                &self.chain_group,
+                &analysis.default_target,
+                "Not reached yet",
+            );
+        }
+        for (depth, timing) in analysis.critical_chain.iter().enumerate() {
+            let row = self.add_row(
+                &self.chain_group,
+                &format!("{}{}", "  ".repeat(depth), timing.name),
+                &format_chain_times(timing),
+            );
+            if timing.duration() >= 1_000_000 {
+                row.add_css_class("error");
+            }
+        }
+
+        let mut blame: Vec<&UnitTiming> = analysis.blame.iter().collect();
+        match self.order.get() {
+            BlameOrder::Duration => {}
+            BlameOrder::Name => blame.sort_by(|a, b| a.name.cmp(&b.name)),
+            BlameOrder::Start => blame.sort_by_key(|timing| timing.activating),
+        }
+        for timing in blame {
+            self.add_row(
+                &self.blame_group,
+                &timing.name,
+                &format_duration(timing.duration()),
+            );
+        }
+    }
+
+    fn add_row(&self, group: &adw::PreferencesGroup, title: &str, value: &str) -> adw::ActionRow {
+        let row = adw::ActionRow::builder()
+            .title(title)
+            .use_markup(false)
+            .build();
+        row.add_suffix(
+            &Label::builder()
+                .label(value)
+                .css_classes(["dim-label", "numeric"])
+                .build(),
+        );
+        group.add(&row);
+        self.rows.borrow_mut().push((group.clone(), row.clone()));
+        row
+    }
+}
+
+fn format_chain_times(timing: &UnitTiming) -> String {
+    match timing.duration() {
+        0 => format!("@{}", format_duration(timing.activated)),
+        duration => format!(
+            "@{} +{}",
+            format_duration(timing.activated),
+            format_duration(duration)
+        ),
+    }
+}
+
+/// Formats microseconds the way `systemd-analyze` does, e.g. `1min 2.345s`
+/// or `87ms`.
+fn format_duration(usec: u64) -> String {
+    let msec = usec / 1_000;
+    match msec {
+        0..1_000 => format!("{}ms", msec),
+        1_000..60_000 => format!("{}.{:03}s", msec / 1_000, msec % 1_000),
+        _ => format!(
+            "{}min {}.{:03}s",
+            msec / 60_000,
+            msec / 1_000 % 60,
+            msec % 1_000
+        ),
+    }
+}
@@ -0,0 +1,475 @@
+use super::{format_scope, show_toast};
+use crate::backend::dependencies::{DependencyGraph, DependencyKind, Direction, ExportFormat};
+use crate::backend::{SystemdServiceManager, UnitScope};
+use adw::prelude::*;
+use adw::{ToastOverlay, ToastPriority};
+use gtk4::{
+    Align, Box, Button, CheckButton, ComboBoxText, Label, ListItem, ListView, Orientation,
+    PolicyType, ScrolledWindow, SignalListItemFactory, SingleSelection, Stack, StringObject,
+    ToggleButton, TreeExpander, TreeListModel, TreeListRow, gio, glib,
+};
+use std::cell::{Cell, RefCell};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// The graph of the last manager read, shared with the row factory.
+type CachedGraph = Rc<RefCell<Option<(UnitScope, Rc<DependencyGraph>)>>>;
+type NavigateCallback = std::boxed::Box<dyn Fn(&str, UnitScope)>;
+
+/// Dependencies tab of the detail pane: a lazily expanded tree of what the
+/// unit pulls in, or of what pulls it in. The graph of a manager is read
+/// once and reused for every unit until the refresh button is pressed.
+pub struct DependencyView {
+    pub container: Box,
+    stack: Stack,
+    status: adw::StatusPage,
+    list_view: ListView,
+    reverse_toggle: ToggleButton,
+    systemd: SystemdServiceManager,
+    toast_overlay: ToastOverlay,
+    unit: RefCell<Option<(String, UnitScope)>>,
+    graph: CachedGraph,
+    stale: Cell<bool>,
+    generation: Cell<u64>,
+    on_navigate: RefCell<Option<NavigateCallback>>,
+}
+
+impl DependencyView {
+    pub fn new(systemd: SystemdServiceManager, toast_overlay: ToastOverlay) -> Rc<Self> {
+        let forward_toggle = ToggleButton::builder()
+            .label("Dependencies")
+            .tooltip_text("Units this unit requires, wants or is ordered after")
+            .active(true)
+            .build();
+        let reverse_toggle = ToggleButton::builder()
+            .label("Dependents")
+            .tooltip_text("Units that require or want this unit")
+            .group(&forward_toggle)
+            .build();
+        let direction_box = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .css_classes(["linked"])
+            .build();
+        direction_box.append(&forward_toggle);
+        direction_box.append(&reverse_toggle);
+
+        let refresh_button = Button::builder()
+            .icon_name("view-refresh-symbolic")
+            .tooltip_text("Re-read the dependencies of all units")
+            .build();
+        let export_button = Button::builder()
+            .icon_name("document-save-symbolic")
+            .tooltip_text("Export as Graphviz DOT or JSON")
+            .build();
+
+        let toolbar = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(6)
+            .margin_top(6)
+            .margin_bottom(6)
+            .margin_start(12)
+            .margin_end(12)
+            .build();
+        toolbar.append(&direction_box);
+        toolbar.append(&Box::builder().hexpand(true).build());
+        toolbar.append(&export_button);
+        toolbar.append(&refresh_button);
+
+        let graph: CachedGraph = Rc::new(RefCell::new(None));
+        let list_view = ListView::builder()
+            .factory(&create_factory(Rc::clone(&graph)))
+            .single_click_activate(false)
+            .css_classes(["navigation-sidebar"])
+            .build();
+        let scroll = ScrolledWindow::builder()
+            .hscrollbar_policy(PolicyType::Never)
+            .child(&list_view)
+            .vexpand(true)
+            .build();
+        let status = adw::StatusPage::builder()
+            .icon_name("network-workgroup-symbolic")
+            .build();
+        let stack = Stack::new();
+        stack.add_named(&scroll, Some("tree"));
+        stack.add_named(&status, Some("status"));
+
+        let container = Box::builder().orientation(Orientation::Vertical).build();
+        container.append(&toolbar);
+        container.append(&stack);
+
+        let view = Rc::new(Self {
+            container,
+            stack,
+            status,
+            list_view,
+            reverse_toggle,
+            systemd,
+            toast_overlay,
+            unit: RefCell::new(None),
+            graph,
+            stale: Cell::new(false),
+            generation: Cell::new(0),
+            on_navigate: RefCell::new(None),
+        });
+
+        let view_map = Rc::clone(&view);
+        view.container.connect_map(move |_| {
+            if view_map.stale.get() {
+                view_map.reload();
+            }
+        });
+
+        let view_toggle = Rc::clone(&view);
+        view.reverse_toggle
+            .connect_toggled(move |_| view_toggle.show_tree());
+
+        let view_refresh = Rc::clone(&view);
+        refresh_button.connect_clicked(move |_| {
+            *view_refresh.graph.borrow_mut() = None;
+            view_refresh.reload();
+        });
+
+        let view_export = Rc::clone(&view);
+        export_button.connect_clicked(move |_| view_export.show_export_dialog());
+
+        let view_activate = Rc::clone(&view);
+        view.list_view.connect_activate(move |list_view, position| {
+            let Some(name) = list_view
+                .model()
+                .and_then(|model| model.item(position))
+                .and_then(|item| item.downcast::<TreeListRow>().ok())
+                .and_then(|row| row.item())
+                .and_then(|item| item.downcast::<StringObject>().ok())
+                .map(|item| split_item(&item.string()).1.to_string())
+            else {
+                return;
+            };
+            let scope = view_activate
+                .unit
+                .borrow()
+                .as_ref()
+                .map(|(_, scope)| *scope);
+            if let (Some(scope), Some(on_navigate)) = (scope, &*view_activate.on_navigate.borrow())
+            {
+                on_navigate(&name, scope);
+            }
+        });
+
+        view
+    }
+
+    /// Called with a unit name when a row of the tree is activated.
+    pub fn connect_navigate(&self, callback: impl Fn(&str, UnitScope) + 'static) {
+        *self.on_navigate.borrow_mut() = Some(std::boxed::Box::new(callback));
+    }
+
+    /// Switches to another unit; the tree is built once the tab is shown.
+    pub fn set_unit(self: &Rc<Self>, unit: Option<(String, UnitScope)>) {
+        *self.unit.borrow_mut() = unit;
+        self.stale.set(true);
+        if self.container.is_mapped() {
+            self.reload();
+        }
+    }
+
+    fn reload(self: &Rc<Self>) {
+        self.stale.set(false);
+        let generation = self.generation.get() + 1;
+        self.generation.set(generation);
+        let Some((_, scope)) = self.unit.borrow().clone() else {
+            return;
+        };
+        if matches!(&*self.graph.borrow(), Some((cached, _)) if *cached == scope) {
+            self.show_tree();
+            return;
+        }
+
+        self.show_status("Reading Dependencies…", None);
+        let view = Rc::clone(self);
+        glib::spawn_future_local(async move {
+            let graph = view
+                .systemd
+                .run(move |systemd| systemd.dependency_graph(scope))
+                .await;
+            if view.generation.get() != generation {
+                return;
+            }
+            match graph {
+                Ok(graph) => {
+                    *view.graph.borrow_mut() = Some((scope, Rc::new(graph)));
+                    view.show_tree();
+                }
+                Err(e) => view.show_status("Could Not Read Dependencies", Some(&e.to_string())),
+            }
+        });
+    }
+
+    fn show_tree(&self) {
+        let Some((unit, _)) = self.unit.borrow().clone() else {
+            return;
+        };
+        let Some(graph) = self
+            .graph
+            .borrow()
+            .as_ref()
+            .map(|(_, graph)| Rc::clone(graph))
+        else {
+            return;
+        };
+        let direction = if self.reverse_toggle.is_active() {
+            Direction::Reverse
+        } else {
+            Direction::Forward
+        };
+
+        let root = children_model(&graph, &unit, direction);
+        if root.n_items() == 0 {
+            self.show_status("No Dependencies", Some(&format!("{} has none", unit)));
+            return;
+        }
+        let model = TreeListModel::new(root, false, false, move |item| {
+            let item = item.downcast_ref::<StringObject>()?;
+            let model = children_model(&graph, split_item(&item.string()).1, direction);
+            (model.n_items() > 0).then(|| model.upcast())
+        });
+        self.list_view
+            .set_model(Some(&SingleSelection::new(Some(model))));
+        self.stack.set_visible_child_name("tree");
+    }
+
+    /// Asks for the format, the units and the edge kinds, then copies the
+    /// export to the clipboard or saves it to the documents folder.
+    fn show_export_dialog(self: &Rc<Self>) {
+        let Some((unit, scope)) = self.unit.borrow().clone() else {
+            return;
+        };
+        let Some(graph) = self
+            .graph
+            .borrow()
+            .as_ref()
+            .filter(|(cached, _)| *cached == scope)
+            .map(|(_, graph)| Rc::clone(graph))
+        else {
+            show_toast(
+                &self.toast_overlay,
+                "Dependencies are still being read",
+                ToastPriority::Normal,
+            );
+            return;
+        };
+
+        let format_combo = ComboBoxText::new();
+        format_combo.append_text("Graphviz DOT");
+        format_combo.append_text("JSON");
+        format_combo.set_active(Some(0));
+        let root_combo = ComboBoxText::new();
+        root_combo.append_text(&format!("{} and what it pulls in", unit));
+        root_combo.append_text(&format!("All {} units", format_scope(scope).to_lowercase()));
+        root_combo.set_active(Some(0));
+        let kind_checks: Vec<(DependencyKind, CheckButton)> = DependencyKind::FORWARD
+            .into_iter()
+            .map(|kind| {
+                let check = CheckButton::builder()
+                    .label(kind.property())
+                    .active(kind != DependencyKind::After)
+                    .build();
+                (kind, check)
+            })
+            .collect();
+
+        let kinds_box = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(6)
+            .build();
+        for (_, check) in &kind_checks {
+            kinds_box.append(check);
+        }
+        let content = Box::builder()
+            .orientation(Orientation::Vertical)
+            .spacing(6)
+            .build();
+        content.append(&format_combo);
+        content.append(&root_combo);
+        content.append(&kinds_box);
+
+        let dialog = adw::AlertDialog::builder()
+            .heading("Export Dependencies")
+            .extra_child(&content)
+            .default_response("save")
+            .close_response("cancel")
+            .build();
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("copy", "Copy");
+        dialog.add_response("save", "Save");
+        dialog.set_response_appearance("save", adw::ResponseAppearance::Suggested);
+
+        let view = Rc::clone(self);
+        dialog.connect_response(None, move |_, response| {
+            if response == "cancel" {
+                return;
+            }
+            let format = match format_combo.active() {
+                Some(1) => ExportFormat::Json,
+                _ => ExportFormat::Dot,
+            };
+            let root = (root_combo.active() == Some(0)).then_some(unit.as_str());
+            let kinds: Vec<DependencyKind> = kind_checks
+                .iter()
+                .filter(|(_, check)| check.is_active())
+                .map(|(kind, _)| *kind)
+                .collect();
+            let export = graph.export(format, root, &kinds);
+
+            if response == "copy" {
+                view.container.clipboard().set_text(&export);
+                show_toast(
+                    &view.toast_overlay,
+                    "Copied dependencies to the clipboard",
+                    ToastPriority::Normal,
+                );
+                return;
+            }
+            let directory = glib::user_special_dir(glib::UserDirectory::Documents)
+                .unwrap_or_else(glib::home_dir);
+            let stem = format!(
+                "dependencies-{}",
+                root.unwrap_or(format_scope(scope)).to_lowercase()
+            );
+            match save_new_file(&directory, &stem, format.extension(), &export) {
+                Ok(path) => show_toast(
+                    &view.toast_overlay,
+                    &format!("Saved to {}", path.display()),
+                    ToastPriority::Normal,
+                ),
+                Err(e) => show_toast(
+                    &view.toast_overlay,
+                    &format!("Failed to save to {}: {}", directory.display(), e),
+                    ToastPriority::High,
+                ),
+            }
+        });
+        dialog.present(Some(&self.container));
+    }
+
+    fn show_status(&self, title: &str, description: Option<&str>) {
+        self.status.set_title(title);
+        self.status.set_description(description);
+        self.stack.set_visible_child_name("status");
+    }
+}
+
+/// Writes `content` to `stem.extension` in `directory`, numbering the name
+/// (`stem-2.extension`, ...) instead of overwriting an earlier export.
+fn save_new_file(
+    directory: &Path,
+    stem: &str,
+    extension: &str,
+    content: &str,
+) -> std::io::Result<PathBuf> {
+    let mut attempt = 1;
+    loop {
+        let file_name = match attempt {
+            1 => format!("{}.{}", stem, extension),
+            n => format!("{}-{}.{}", stem, n, extension),
+        };
+        let path = directory.join(file_name);
+        match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(mut file) => {
+                file.write_all(content.as_bytes())?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => attempt += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Tree items are `StringObject`s holding `kind\tunit`.
+fn children_model(graph: &DependencyGraph, unit: &str, direction: Direction) -> gio::ListStore {
+    let store = gio::ListStore::new::<StringObject>();
+    for (kind, other) in graph.dependencies(unit, direction) {
+        store.append(&StringObject::new(&format!(
+            "{}\t{}",
+            kind.property(),
+            other
+        )));
+    }
+    store
+}
+
+fn split_item(item: &str) -> (&str, &str) {
+    item.split_once('\t').unwrap_or(("", item))
+}
+
+fn create_factory(graph: CachedGraph) -> SignalListItemFactory {
+    let factory = SignalListItemFactory::new();
+    factory.connect_setup(|_, item| {
+        let Some(item) = item.downcast_ref::<ListItem>() else {
+            return;
+        };
+        let content = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(6)
+            .build();
+        content.append(
+            &Label::builder()
+                .width_chars(11)
+                .xalign(0.0)
+                .css_classes(["caption", "dim-label"])
+                .build(),
+        );
+        content.append(&Label::builder().halign(Align::Start).build());
+        content.append(
+            &Label::builder()
+                .halign(Align::Start)
+                .css_classes(["caption"])
+                .build(),
+        );
+        item.set_child(Some(&TreeExpander::builder().child(&content).build()));
+    });
+    factory.connect_bind(move |_, item| {
+        let Some(item) = item.downcast_ref::<ListItem>() else {
+            return;
+        };
+        let Some(row) = item.item().and_then(|i| i.downcast::<TreeListRow>().ok()) else {
+            return;
+        };
+        let Some(expander) = item.child().and_then(|c| c.downcast::<TreeExpander>().ok()) else {
+            return;
+        };
+        let Some(value) = row.item().and_then(|i| i.downcast::<StringObject>().ok()) else {
+            return;
+        };
+        expander.set_list_row(Some(&row));
+
+        let value = value.string();
+        let (kind, unit) = split_item(&value);
+        let state = graph
+            .borrow()
+            .as_ref()
+            .and_then(|(_, graph)| graph.active_state(unit).map(str::to_string));
+        let labels: Vec<Label> =
+            std::iter::successors(expander.child().and_then(|c| c.first_child()), |w| {
+                w.next_sibling()
+            })
+            .filter_map(|w| w.downcast::<Label>().ok())
+            .collect();
+        if let [kind_label, unit_label, state_label] = labels.as_slice() {
+            kind_label.set_label(kind);
+            unit_label.set_label(unit);
+            state_label.set_label(state.as_deref().unwrap_or("not loaded"));
+            let class = match state.as_deref() {
+                Some("active") => "success",
+                Some("failed") => "error",
+                _ => "dim-label",
+            };
+            state_label.set_css_classes(&["caption", class]);
+        }
+    });
+    factory
+}
@@ -0,0 +1,278 @@
+use super::dependencies::DependencyView;
+use super::logs::LogView;
+use super::processes::ProcessView;
+use super::unit_file::UnitFileView;
+use crate::backend::{SystemdServiceManager, UnitDetails, UnitScope};
+use adw::ToastOverlay;
+use adw::prelude::*;
+use gtk4::{Box, Orientation, Stack, glib};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Right-hand pane showing the selected unit, with a properties tab, a
+/// journal tab, a dependencies tab, a processes tab and a unit file tab.
+pub struct DetailPane {
+    pub container: Stack,
+    page: adw::PreferencesPage,
+    groups: RefCell<Vec<adw::PreferencesGroup>>,
+    status: adw::StatusPage,
+    view_stack: adw::ViewStack,
+    log_view: Rc<LogView>,
+    dependency_view: Rc<DependencyView>,
+    process_view: Rc<ProcessView>,
+    unit_file_view: Rc<UnitFileView>,
+}
+
+impl DetailPane {
+    pub fn new(systemd: SystemdServiceManager, toast_overlay: ToastOverlay) -> Self {
+        let page = adw::PreferencesPage::new();
+        let status = adw::StatusPage::builder()
+            .icon_name("system-run-symbolic")
+            .title("No Service Selected")
+            .description("Select a service to see its properties")
+            .build();
+        let log_view = LogView::new();
+        let dependency_view = DependencyView::new(systemd.clone(), toast_overlay.clone());
+        let process_view = ProcessView::new(systemd.clone(), toast_overlay.clone());
+        let unit_file_view = UnitFileView::new(systemd, toast_overlay);
+
+        let view_stack = adw::ViewStack::builder().vexpand(true).build();
+        view_stack.add_titled_with_icon(
+            &page,
+            Some("properties"),
+            "Properties",
+            "document-properties-symbolic",
+        );
+        view_stack.add_titled_with_icon(
+            &log_view.container,
+            Some("logs"),
+            "Logs",
+            "utilities-terminal-symbolic",
+        );
+        view_stack.add_titled_with_icon(
+            &dependency_view.container,
+            Some("dependencies"),
+            "Dependencies",
+            "network-workgroup-symbolic",
+        );
+        view_stack.add_titled_with_icon(
+            &process_view.container,
+            Some("processes"),
+            "Processes",
+            "utilities-system-monitor-symbolic",
+        );
+        view_stack.add_titled_with_icon(
+            &unit_file_view.container,
+            Some("unit-file"),
+            "Unit File",
+            "text-x-generic-symbolic",
+        );
+        let switcher = adw::ViewSwitcher::builder()
+            .stack(&view_stack)
+            .policy(adw::ViewSwitcherPolicy::Wide)
+            .margin_top(6)
+            .margin_bottom(6)
+            .build();
+
+        let unit_box = Box::builder().orientation(Orientation::Vertical).build();
+        unit_box.append(&switcher);
+        unit_box.append(&view_stack);
+
+        let container = Stack::builder().width_request(420).vexpand(true).build();
+        container.add_named(&status, Some("empty"));
+        container.add_named(&unit_box, Some("details"));
+        container.set_visible_child_name("empty");
+
+        Self {
+            container,
+            page,
+            groups: RefCell::new(Vec::new()),
+            status,
+            view_stack,
+            log_view,
+            dependency_view,
+            process_view,
+            unit_file_view,
+        }
+    }
+
+    /// Points the journal, dependencies, processes and unit file tabs at the
+    /// selected unit, or at nothing.
+    pub fn set_unit(&self, unit: Option<(String, UnitScope)>) {
+        self.log_view.set_unit(unit.clone());
+        self.dependency_view.set_unit(unit.clone());
+        self.process_view.set_unit(unit.clone());
+        self.unit_file_view.set_unit(unit);
+    }
+
+    /// Called with a unit picked in the dependency tree.
+    pub fn connect_navigate(&self, callback: impl Fn(&str, UnitScope) + 'static) {
+        self.dependency_view.connect_navigate(callback);
+    }
+
+    pub fn show_logs(&self) {
+        self.view_stack.set_visible_child_name("logs");
+    }
+
+    pub fn clear(&self) {
+        self.status.set_title("No Service Selected");
+        self.status
+            .set_description(Some("Select a service to see its properties"));
+        self.container.set_visible_child_name("empty");
+    }
+
+    pub fn show_error(&self, message: &str) {
+        self.status.set_title("Could Not Load Properties");
+        self.status.set_description(Some(message));
+        self.container.set_visible_child_name("empty");
+    }
+
+    pub fn show_details(&self, details: &UnitDetails) {
+        for group in self.groups.borrow_mut().drain(..) {
+            self.page.remove(&group);
+        }
+
+        self.add_group(
+            "Unit",
+            &[
+                ("Name", details.id.clone()),
+                ("Description", details.description.clone()),
+                ("Load State", details.load_state.clone()),
+                (
+                    "Active State",
+                    format!("{} ({})", details.active_state, details.sub_state),
+                ),
+                ("Unit File State", details.unit_file_state.clone()),
+                ("Fragment Path", details.fragment_path.clone()),
+            ],
+        );
+
+        self.add_group(
+            "Timestamps",
+            &[
+                (
+                    "Active Since",
+                    format_timestamp(details.active_enter_timestamp),
+                ),
+                (
+                    "Last Deactivated",
+                    format_timestamp(details.active_exit_timestamp),
+                ),
+                (
+                    "Activation Started",
+                    format_timestamp(details.inactive_exit_timestamp),
+                ),
+                (
+                    "Last State Change",
+                    format_timestamp(details.state_change_timestamp),
+                ),
+            ],
+        );
+
+        if let Some(service) = &details.service {
+            let mut rows = vec![
+                ("Type", service.service_type.clone()),
+                ("Restart Policy", service.restart.clone()),
+                ("Result", service.result.clone()),
+                ("Main PID", format_pid(service.main_pid)),
+                ("Restarts", service.n_restarts.to_string()),
+            ];
+            rows.extend(
+                service
+                    .exec_start
+                    .iter()
+                    .map(|command| ("ExecStart", command.clone())),
+            );
+            self.add_group("Service", &rows);
+
+            self.add_group(
+                "Resources",
+                &[
+                    (
+                        "Memory",
+                        format_optional(service.memory_current, format_bytes),
+                    ),
+                    (
+                        "CPU Time",
+                        format_optional(service.cpu_usage_nsec, format_cpu_time),
+                    ),
+                    (
+                        "Tasks",
+                        format_optional(service.tasks_current, |t| t.to_string()),
+                    ),
+                ],
+            );
+        }
+
+        self.container.set_visible_child_name("details");
+    }
+
+    fn add_group(&self, title: &str, rows: &[(&str, String)]) {
+        let group = adw::PreferencesGroup::builder().title(title).build();
+        for (key, value) in rows {
+            group.add(&create_property_row(key, value));
+        }
+        self.page.add(&group);
+        self.groups.borrow_mut().push(group);
+    }
+}
+
+fn create_property_row(key: &str, value: &str) -> adw::ActionRow {
+    adw::ActionRow::builder()
+        .title(key)
+        .subtitle(if value.is_empty() { "—" } else { value })
+        .use_markup(false)
+        .subtitle_selectable(true)
+        .css_classes(["property"])
+        .build()
+}
+
+pub fn format_timestamp(usec: u64) -> String {
+    if usec == 0 {
+        return "Never".to_string();
+    }
+    glib::DateTime::from_unix_local((usec / 1_000_000) as i64)
+        .and_then(|dt| dt.format("%Y-%m-%d %H:%M:%S"))
+        .map(|s| s.to_string())
+        .unwrap_or_else(|_| usec.to_string())
+}
+
+fn format_pid(pid: u32) -> String {
+    if pid == 0 {
+        "—".to_string()
+    } else {
+        pid.to_string()
+    }
+}
+
+fn format_optional(value: Option<u64>, format: impl Fn(u64) -> String) -> String {
+    value
+        .map(format)
+        .unwrap_or_else(|| "Not tracked".to_string())
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+pub fn format_cpu_time(nsec: u64) -> String {
+    let msec = nsec / 1_000_000;
+    if msec < 1000 {
+        format!("{}ms", msec)
+    } else if msec < 60_000 {
+        format!("{:.3}s", msec as f64 / 1000.0)
+    } else {
+        format!("{}min {}s", msec / 60_000, (msec % 60_000) / 1000)
+    }
+}
@@ -0,0 +1,317 @@
+use super::detail::format_timestamp;
+use super::{format_scope, show_toast};
+use crate::backend::failed::{FailedUnit, MainExit};
+use crate::backend::{SystemdServiceManager, signal_name};
+use adw::prelude::*;
+use adw::{ToastOverlay, ToastPriority};
+use gtk4::{Align, Box, Button, CheckButton, Orientation, glib};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+type ShowLogsCallback = std::boxed::Box<dyn Fn(&str, crate::backend::UnitScope)>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BulkAction {
+    ResetFailed,
+    Restart,
+}
+
+/// Page listing the failed units of both managers with why and when they
+/// failed. Checked rows can be reset or restarted together.
+pub struct FailedView {
+    pub container: adw::PreferencesPage,
+    group: adw::PreferencesGroup,
+    /// Shown instead of the rows while nothing has failed.
+    empty_row: adw::ActionRow,
+    rows: RefCell<Vec<(adw::ActionRow, CheckButton, FailedUnit)>>,
+    select_all: CheckButton,
+    reset_button: Button,
+    restart_button: Button,
+    systemd: SystemdServiceManager,
+    toast_overlay: ToastOverlay,
+    on_show_logs: RefCell<Option<ShowLogsCallback>>,
+}
+
+impl FailedView {
+    pub fn new(systemd: SystemdServiceManager, toast_overlay: ToastOverlay) -> Rc<Self> {
+        let select_all = CheckButton::builder()
+            .tooltip_text("Select all")
+            .valign(Align::Center)
+            .build();
+        let reset_button = Button::builder()
+            .label("Reset Failed")
+            .tooltip_text("Clear the failed state of the selected units")
+            .sensitive(false)
+            .build();
+        let restart_button = Button::builder()
+            .label("Restart")
+            .tooltip_text("Restart the selected units")
+            .sensitive(false)
+            .build();
+        let refresh_button = Button::builder()
+            .icon_name("view-refresh-symbolic")
+            .tooltip_text("Refresh failed units")
+            .css_classes(["flat"])
+            .build();
+        let header = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(6)
+            .valign(Align::Center)
+            .build();
+        header.append(&select_all);
+        header.append(&reset_button);
+        header.append(&restart_button);
+        header.append(&refresh_button);
+
+        let group = adw::PreferencesGroup::builder()
+            .title("Failed Units")
+            .description("Most recent failure first")
+            .header_suffix(&header)
+            .build();
+        let empty_row = adw::ActionRow::builder()
+            .title("No failed units")
+            .css_classes(["dim-label"])
+            .build();
+        group.add(&empty_row);
+        let container = adw::PreferencesPage::new();
+        container.add(&group);
+
+        let view = Rc::new(Self {
+            container,
+            group,
+            empty_row,
+            rows: RefCell::new(Vec::new()),
+            select_all,
+            reset_button,
+            restart_button,
+            systemd,
+            toast_overlay,
+            on_show_logs: RefCell::new(None),
+        });
+
+        let view_refresh = Rc::clone(&view);
+        refresh_button.connect_clicked(move |_| view_refresh.refresh());
+
+        let view_select = Rc::clone(&view);
+        view.select_all.connect_toggled(move |check| {
+            for (_, row_check, _) in view_select.rows.borrow().iter() {
+                row_check.set_active(check.is_active());
+            }
+        });
+
+        for (button, action) in [
+            (&view.reset_button, BulkAction::ResetFailed),
+            (&view.restart_button, BulkAction::Restart),
+        ] {
+            let view_action = Rc::clone(&view);
+            button.connect_clicked(move |_| view_action.run_bulk(action));
+        }
+
+        view.show_units(Vec::new());
+        view
+    }
+
+    /// Called with a unit whose journal should be shown.
+    pub fn connect_show_logs(&self, callback: impl Fn(&str, crate::backend::UnitScope) + 'static) {
+        *self.on_show_logs.borrow_mut() = Some(std::boxed::Box::new(callback));
+    }
+
+    /// Whether the unit is currently listed, so a change to it may mean it
+    /// recovered.
+    pub fn lists(&self, name: &str, scope: crate::backend::UnitScope) -> bool {
+        self.rows
+            .borrow()
+            .iter()
+            .any(|(_, _, unit)| unit.name == name && unit.scope == scope)
+    }
+
+    pub fn refresh(self: &Rc<Self>) {
+        let view = Rc::clone(self);
+        glib::spawn_future_local(async move {
+            let units = view
+                .systemd
+                .run(|systemd| systemd.list_failed_units())
+                .await;
+            match units {
+                Ok(failed) => {
+                    view.show_units(failed.units);
+                    for (scope, e) in failed.unreachable {
+                        show_toast(
+                            &view.toast_overlay,
+                            &format!(
+                                "Failed to list failed units of the {} manager: {}",
+                                format_scope(scope).to_lowercase(),
+                                e
+                            ),
+                            ToastPriority::Normal,
+                        );
+                    }
+                }
+                Err(e) => show_toast(
+                    &view.toast_overlay,
+                    &format!("Failed to list failed units: {}", e),
+                    ToastPriority::High,
+                ),
+            }
+        });
+    }
+
+    fn show_units(self: &Rc<Self>, units: Vec<FailedUnit>) {
+        let checked: Vec<(String, crate::backend::UnitScope)> = self
+            .checked_units()
+            .into_iter()
+            .map(|unit| (unit.name, unit.scope))
+            .collect();
+        for (row, ..) in self.rows.borrow_mut().drain(..) {
+            self.group.remove(&row);
+        }
+        self.select_all.set_active(false);
+        self.select_all.set_sensitive(!units.is_empty());
+        self.empty_row.set_visible(units.is_empty());
+
+        for unit in units {
+            let row = adw::ActionRow::builder()
+                .title(unit.name.as_str())
+                .subtitle(format_failure(&unit))
+                .use_markup(false)
+                .build();
+            let check = CheckButton::builder()
+                .valign(Align::Center)
+                .active(checked.contains(&(unit.name.clone(), unit.scope)))
+                .build();
+            row.add_prefix(&check);
+            row.set_activatable_widget(Some(&check));
+
+            let logs_button = Button::builder()
+                .icon_name("utilities-terminal-symbolic")
+                .tooltip_text("Show logs")
+                .valign(Align::Center)
+                .css_classes(["flat"])
+                .build();
+            let view_logs = Rc::clone(self);
+            let (name, scope) = (unit.name.clone(), unit.scope);
+            logs_button.connect_clicked(move |_| {
+                if let Some(callback) = view_logs.on_show_logs.borrow().as_ref() {
+                    callback(&name, scope);
+                }
+            });
+            row.add_suffix(&logs_button);
+
+            let view_check = Rc::clone(self);
+            check.connect_toggled(move |_| view_check.update_buttons());
+
+            self.group.add(&row);
+            self.rows.borrow_mut().push((row, check, unit));
+        }
+        self.update_buttons();
+    }
+
+    fn checked_units(&self) -> Vec<FailedUnit> {
+        self.rows
+            .borrow()
+            .iter()
+            .filter(|(_, check, _)| check.is_active())
+            .map(|(_, _, unit)| unit.clone())
+            .collect()
+    }
+
+    fn update_buttons(&self) {
+        let any_checked = !self.checked_units().is_empty();
+        self.reset_button.set_sensitive(any_checked);
+        self.restart_button.set_sensitive(any_checked);
+    }
+
+    /// Resets or restarts the checked units one after another, then reads
+    /// the list again.
+    fn run_bulk(self: &Rc<Self>, action: BulkAction) {
+        let units = self.checked_units();
+        if units.is_empty() {
+            return;
+        }
+        self.reset_button.set_sensitive(false);
+        self.restart_button.set_sensitive(false);
+
+        let view = Rc::clone(self);
+        glib::spawn_future_local(async move {
+            let count = units.len();
+            let mut failures = Vec::new();
+            for unit in units {
+                let (name, scope) = (unit.name.clone(), unit.scope);
+                let result = view
+                    .systemd
+                    .run(move |systemd| match action {
+                        BulkAction::ResetFailed => systemd
+                            .reset_failed_unit(&name, scope)
+                            .map_err(|e| e.to_string()),
+                        BulkAction::Restart => match systemd.restart_unit(&name, scope) {
+                            Ok(result) if result.is_success() => Ok(()),
+                            Ok(result) => Err(result.describe().to_string()),
+                            Err(e) => Err(e.to_string()),
+                        },
+                    })
+                    .await;
+                if let Err(e) = result {
+                    failures.push(format!("{}: {}", unit.name, e));
+                }
+            }
+
+            let verb = match action {
+                BulkAction::ResetFailed => "reset",
+                BulkAction::Restart => "restart",
+            };
+            if failures.is_empty() {
+                let done = match action {
+                    BulkAction::ResetFailed => "Reset",
+                    BulkAction::Restart => "Restarted",
+                };
+                show_toast(
+                    &view.toast_overlay,
+                    &format!(
+                        "{} {} unit{}",
+                        done,
+                        count,
+                        if count == 1 { "" } else { "s" }
+                    ),
+                    ToastPriority::Normal,
+                );
+            } else {
+                show_toast(
+                    &view.toast_overlay,
+                    &format!("Failed to {} {}", verb, failures.join(", ")),
+                    ToastPriority::High,
+                );
+            }
+            view.refresh();
+        });
+    }
+}
+
+fn format_failure(unit: &FailedUnit) -> String {
+    let mut parts = Vec::new();
+    if !unit.description.is_empty() && unit.description != unit.name {
+        parts.push(unit.description.clone());
+    }
+    if !unit.result.is_empty() {
+        parts.push(format!("Result: {}", unit.result));
+    }
+    if let Some(exit) = unit.main_exit {
+        parts.push(format_main_exit(exit));
+    }
+    parts.push(format!("Failed {}", format_timestamp(unit.failed_at)));
+    parts.push(format_scope(unit.scope).to_string());
+    parts.join(" · ")
+}
+
+fn format_main_exit(exit: MainExit) -> String {
+    match exit {
+        MainExit::Exited(status) => format!("Exit status {}", status),
+        MainExit::Killed(signal) => format!("Killed by {}", format_signal(signal)),
+        MainExit::Dumped(signal) => format!("Dumped core on {}", format_signal(signal)),
+    }
+}
+
+fn format_signal(number: i32) -> String {
+    signal_name(number)
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("signal {}", number))
+}
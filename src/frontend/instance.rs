@@ -0,0 +1,71 @@
+use super::ServiceAction;
+use crate::backend::templates;
+use adw::prelude::*;
+use gtk4::{Box, Entry, Label, Orientation};
+
+/// Asks for the instance string of `template` and hands the escaped unit
+/// name to `on_create` together with the chosen action, Start or Enable.
+/// The name is previewed while typing so the escaping is visible up front.
+pub fn show_new_instance_dialog(
+    parent: &impl IsA<gtk4::Widget>,
+    template: &str,
+    on_create: impl Fn(String, ServiceAction) + 'static,
+) {
+    let entry = Entry::builder()
+        .placeholder_text("Instance, e.g. tty2 or /dev/sda1")
+        .activates_default(true)
+        .build();
+    let preview = Label::builder()
+        .halign(gtk4::Align::Start)
+        .wrap(true)
+        .selectable(true)
+        .css_classes(["caption", "dim-label"])
+        .build();
+    let content = Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(6)
+        .build();
+    content.append(&entry);
+    content.append(&preview);
+
+    let dialog = adw::AlertDialog::builder()
+        .heading(format!("New Instance of {}", template))
+        .body("The instance string is escaped like systemd-escape does.")
+        .extra_child(&content)
+        .default_response("start")
+        .close_response("cancel")
+        .build();
+    dialog.add_response("cancel", "Cancel");
+    dialog.add_response("enable", "Enable");
+    dialog.add_response("start", "Start");
+    dialog.set_response_appearance("start", adw::ResponseAppearance::Suggested);
+
+    let update_preview = {
+        let (dialog, preview, template) = (dialog.clone(), preview.clone(), template.to_string());
+        move |entry: &Entry| {
+            let name = templates::instance_name(&template, &entry.text());
+            let valid = name.is_ok();
+            preview.set_label(&match name {
+                Ok(name) => name,
+                Err(e) => e.to_string(),
+            });
+            dialog.set_response_enabled("start", valid);
+            dialog.set_response_enabled("enable", valid);
+        }
+    };
+    update_preview(&entry);
+    entry.connect_changed(update_preview);
+
+    let template = template.to_string();
+    dialog.connect_response(None, move |_, response| {
+        let action = match response {
+            "start" => ServiceAction::Start,
+            "enable" => ServiceAction::Enable,
+            _ => return,
+        };
+        if let Ok(name) = templates::instance_name(&template, &entry.text()) {
+            on_create(name, action);
+        }
+    });
+    dialog.present(Some(parent));
+}
@@ -0,0 +1,175 @@
+use super::{format_scope, show_toast};
+use crate::backend::jobs::JobInfo;
+use crate::backend::{SystemdServiceManager, UnitScope};
+use adw::prelude::*;
+use adw::{ToastOverlay, ToastPriority};
+use gtk4::{Align, Box, Button, Orientation, glib};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Sidebar group listing the job queues of both managers. It is refreshed
+/// whenever a `JobNew`/`JobRemoved` signal arrives.
+pub struct JobsPanel {
+    pub container: Box,
+    group: adw::PreferencesGroup,
+    rows: RefCell<Vec<adw::ActionRow>>,
+    systemd: SystemdServiceManager,
+    toast_overlay: ToastOverlay,
+}
+
+impl JobsPanel {
+    pub fn new(systemd: SystemdServiceManager, toast_overlay: ToastOverlay) -> Rc<Self> {
+        let clear_button = Button::builder()
+            .label("Cancel All")
+            .valign(Align::Center)
+            .css_classes(["flat", "destructive-action"])
+            .build();
+        let group = adw::PreferencesGroup::builder()
+            .title("Pending Jobs")
+            .description("Running and waiting systemd jobs")
+            .header_suffix(&clear_button)
+            .build();
+
+        let container = Box::builder()
+            .orientation(Orientation::Vertical)
+            .margin_top(12)
+            .margin_bottom(12)
+            .margin_start(12)
+            .margin_end(12)
+            .build();
+        container.append(&group);
+
+        let panel = Rc::new(Self {
+            container,
+            group,
+            rows: RefCell::new(Vec::new()),
+            systemd,
+            toast_overlay,
+        });
+
+        let panel_clear = Rc::clone(&panel);
+        clear_button.connect_clicked(move |_| panel_clear.confirm_clear_jobs());
+
+        panel.show_jobs(&[]);
+        panel
+    }
+
+    pub fn refresh(self: &Rc<Self>) {
+        let panel = Rc::clone(self);
+        glib::spawn_future_local(async move {
+            let jobs = panel.systemd.run(|systemd| systemd.list_jobs()).await;
+            panel.show_jobs(&jobs.unwrap_or_default());
+        });
+    }
+
+    fn show_jobs(self: &Rc<Self>, jobs: &[JobInfo]) {
+        for row in self.rows.borrow_mut().drain(..) {
+            self.group.remove(&row);
+        }
+
+        if jobs.is_empty() {
+            let row = adw::ActionRow::builder()
+                .title("No pending jobs")
+                .css_classes(["dim-label"])
+                .build();
+            self.group.add(&row);
+            self.rows.borrow_mut().push(row);
+            return;
+        }
+
+        for job in jobs {
+            let row = adw::ActionRow::builder()
+                .title(job.unit.as_str())
+                .subtitle(format!(
+                    "#{} · {} · {} · {}",
+                    job.id,
+                    job.job_type,
+                    job.state,
+                    format_scope(job.scope)
+                ))
+                .use_markup(false)
+                .build();
+            let cancel_button = Button::builder()
+                .icon_name("process-stop-symbolic")
+                .tooltip_text("Cancel job")
+                .valign(Align::Center)
+                .css_classes(["flat"])
+                .build();
+            let panel = Rc::clone(self);
+            let job = job.clone();
+            cancel_button.connect_clicked(move |_| panel.cancel_job(&job));
+            row.add_suffix(&cancel_button);
+            self.group.add(&row);
+            self.rows.borrow_mut().push(row);
+        }
+    }
+
+    fn cancel_job(self: &Rc<Self>, job: &JobInfo) {
+        let panel = Rc::clone(self);
+        let job = job.clone();
+        glib::spawn_future_local(async move {
+            let (id, scope) = (job.id, job.scope);
+            let result = panel
+                .systemd
+                .run(move |systemd| systemd.cancel_job(id, scope))
+                .await;
+            match result {
+                Ok(()) => show_toast(
+                    &panel.toast_overlay,
+                    &format!("Canceled {} job for {}", job.job_type, job.unit),
+                    ToastPriority::Normal,
+                ),
+                Err(e) => show_toast(
+                    &panel.toast_overlay,
+                    &format!("Failed to cancel job #{}: {}", job.id, e),
+                    ToastPriority::High,
+                ),
+            }
+            panel.refresh();
+        });
+    }
+
+    /// `ClearJobs` also drops jobs that other tools queued, so it is only
+    /// sent once confirmed.
+    fn confirm_clear_jobs(self: &Rc<Self>) {
+        let dialog = adw::AlertDialog::builder()
+            .heading("Cancel All Jobs?")
+            .body("Every queued job of the system and the user manager is canceled, including jobs queued by other programs. Units that are starting or stopping are left where they are.")
+            .default_response("cancel")
+            .close_response("cancel")
+            .build();
+        dialog.add_response("cancel", "Keep Jobs");
+        dialog.add_response("clear", "Cancel All");
+        dialog.set_response_appearance("clear", adw::ResponseAppearance::Destructive);
+        let panel = Rc::clone(self);
+        dialog.connect_response(Some("clear"), move |_, _| panel.clear_jobs());
+        dialog.present(Some(&self.container));
+    }
+
+    fn clear_jobs(self: &Rc<Self>) {
+        let panel = Rc::clone(self);
+        glib::spawn_future_local(async move {
+            let results = panel
+                .systemd
+                .run(|systemd| {
+                    [UnitScope::System, UnitScope::User]
+                        .map(|scope| (scope, systemd.clear_jobs(scope)))
+                })
+                .await;
+            for (scope, result) in results {
+                if let Err(e) = result {
+                    show_toast(
+                        &panel.toast_overlay,
+                        &format!(
+                            "Failed to cancel {} jobs: {}",
+                            format_scope(scope).to_lowercase(),
+                            e
+                        ),
+                        ToastPriority::High,
+                    );
+                }
+            }
+            panel.refresh();
+        });
+    }
+}
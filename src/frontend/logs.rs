@@ -0,0 +1,328 @@
+use super::detail::format_timestamp;
+use crate::backend::UnitScope;
+use crate::backend::journal::{self, JournalEntry, JournalFollower, JournalQuery, Priority};
+use crate::backend::worker;
+use adw::prelude::*;
+use gtk4::{
+    Align, Box, Button, ComboBoxText, Orientation, PolicyType, ScrolledWindow, TextBuffer,
+    TextMark, TextTag, TextView, ToggleButton, WrapMode, glib,
+};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SINCE_OPTIONS: [(&str, Option<u64>); 5] = [
+    ("Last 15 minutes", Some(15 * 60)),
+    ("Last hour", Some(60 * 60)),
+    ("Last 24 hours", Some(24 * 60 * 60)),
+    ("Last 7 days", Some(7 * 24 * 60 * 60)),
+    ("All time", None),
+];
+
+/// Journal tab of the detail pane. Logs are only read while the tab is
+/// mapped, so selecting a unit does not spawn `journalctl` needlessly.
+pub struct LogView {
+    pub container: Box,
+    buffer: TextBuffer,
+    text_view: TextView,
+    priority_combo: ComboBoxText,
+    since_combo: ComboBoxText,
+    follow_toggle: ToggleButton,
+    unit: RefCell<Option<(String, UnitScope)>>,
+    stale: Cell<bool>,
+    /// Bumped on every reload so a slow read for a previous unit or filter
+    /// does not overwrite a newer one.
+    generation: Cell<u64>,
+    last_failure: RefCell<Option<TextMark>>,
+    follower: RefCell<Option<(JournalFollower, glib::SourceId)>>,
+}
+
+impl LogView {
+    pub fn new() -> Rc<Self> {
+        let buffer = TextBuffer::new(None);
+        for priority in Priority::ALL {
+            let tag = TextTag::builder().name(priority.label()).build();
+            match priority {
+                Priority::Emergency | Priority::Alert | Priority::Critical | Priority::Error => {
+                    tag.set_foreground(Some("#e01b24"));
+                    tag.set_weight(700);
+                }
+                Priority::Warning => tag.set_foreground(Some("#e66100")),
+                Priority::Notice => tag.set_weight(700),
+                Priority::Info => {}
+                Priority::Debug => tag.set_foreground(Some("#77767b")),
+            }
+            buffer.tag_table().add(&tag);
+        }
+
+        let text_view = TextView::builder()
+            .buffer(&buffer)
+            .editable(false)
+            .cursor_visible(false)
+            .monospace(true)
+            .wrap_mode(WrapMode::WordChar)
+            .left_margin(12)
+            .right_margin(12)
+            .top_margin(6)
+            .bottom_margin(6)
+            .build();
+
+        let priority_combo = ComboBoxText::builder()
+            .valign(Align::Center)
+            .tooltip_text("Least severe priority to show")
+            .build();
+        for priority in Priority::ALL {
+            priority_combo.append_text(priority.label());
+        }
+        priority_combo.set_active(Some(Priority::Debug as u32));
+
+        let since_combo = ComboBoxText::builder().valign(Align::Center).build();
+        for (label, _) in SINCE_OPTIONS {
+            since_combo.append_text(label);
+        }
+        since_combo.set_active(Some(2));
+
+        let follow_toggle = ToggleButton::builder()
+            .icon_name("media-playback-start-symbolic")
+            .tooltip_text("Follow new entries")
+            .build();
+        let jump_button = Button::builder()
+            .icon_name("dialog-error-symbolic")
+            .tooltip_text("Jump to last failure")
+            .build();
+        let refresh_button = Button::builder()
+            .icon_name("view-refresh-symbolic")
+            .tooltip_text("Reload logs")
+            .build();
+
+        let toolbar = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(6)
+            .margin_top(6)
+            .margin_bottom(6)
+            .margin_start(12)
+            .margin_end(12)
+            .build();
+        toolbar.append(&priority_combo);
+        toolbar.append(&since_combo);
+        let spacer = Box::builder().hexpand(true).build();
+        toolbar.append(&spacer);
+        toolbar.append(&jump_button);
+        toolbar.append(&follow_toggle);
+        toolbar.append(&refresh_button);
+
+        let scroll = ScrolledWindow::builder()
+            .hscrollbar_policy(PolicyType::Never)
+            .child(&text_view)
+            .vexpand(true)
+            .build();
+
+        let container = Box::builder().orientation(Orientation::Vertical).build();
+        container.append(&toolbar);
+        container.append(&scroll);
+
+        let view = Rc::new(Self {
+            container,
+            buffer,
+            text_view,
+            priority_combo,
+            since_combo,
+            follow_toggle,
+            unit: RefCell::new(None),
+            stale: Cell::new(false),
+            generation: Cell::new(0),
+            last_failure: RefCell::new(None),
+            follower: RefCell::new(None),
+        });
+
+        let view_map = Rc::clone(&view);
+        view.container.connect_map(move |_| {
+            if view_map.stale.get() {
+                view_map.reload();
+            }
+        });
+
+        let view_priority = Rc::clone(&view);
+        view.priority_combo
+            .connect_changed(move |_| view_priority.reload());
+
+        let view_since = Rc::clone(&view);
+        view.since_combo
+            .connect_changed(move |_| view_since.reload());
+
+        let view_refresh = Rc::clone(&view);
+        refresh_button.connect_clicked(move |_| view_refresh.reload());
+
+        let view_jump = Rc::clone(&view);
+        jump_button.connect_clicked(move |_| view_jump.jump_to_last_failure());
+
+        let view_follow = Rc::clone(&view);
+        view.follow_toggle.connect_toggled(move |toggle| {
+            if toggle.is_active() {
+                view_follow.start_following();
+            } else {
+                view_follow.stop_following();
+            }
+        });
+
+        view
+    }
+
+    /// Switches to another unit; its logs are read once the tab is shown.
+    pub fn set_unit(self: &Rc<Self>, unit: Option<(String, UnitScope)>) {
+        self.stop_following();
+        self.follow_toggle.set_active(false);
+        self.buffer.set_text("");
+        *self.last_failure.borrow_mut() = None;
+        *self.unit.borrow_mut() = unit;
+        self.generation.set(self.generation.get() + 1);
+        self.stale.set(true);
+        if self.container.is_mapped() {
+            self.reload();
+        }
+    }
+
+    fn query(&self) -> Option<JournalQuery> {
+        let (unit, scope) = self.unit.borrow().clone()?;
+        let mut query = JournalQuery::new(&unit, scope);
+        query.max_priority = self
+            .priority_combo
+            .active()
+            .and_then(|i| Priority::ALL.get(i as usize).copied())
+            .unwrap_or(Priority::Debug);
+        query.since = self
+            .since_combo
+            .active()
+            .and_then(|i| SINCE_OPTIONS.get(i as usize))
+            .and_then(|(_, seconds)| *seconds)
+            .map(|seconds| {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default();
+                now.saturating_sub(Duration::from_secs(seconds)).as_micros() as u64
+            });
+        Some(query)
+    }
+
+    fn reload(self: &Rc<Self>) {
+        self.stale.set(false);
+        self.buffer.set_text("");
+        *self.last_failure.borrow_mut() = None;
+        let generation = self.generation.get() + 1;
+        self.generation.set(generation);
+        let Some(query) = self.query() else {
+            return;
+        };
+        self.buffer.set_text("Loading…");
+
+        let view = Rc::clone(self);
+        glib::spawn_future_local(async move {
+            let entries = worker::spawn(move || journal::read_entries(&query)).await;
+            if view.generation.get() != generation {
+                return;
+            }
+            view.buffer.set_text("");
+            match entries {
+                Ok(entries) if entries.is_empty() => {
+                    view.buffer.set_text("No journal entries for this unit.");
+                }
+                Ok(entries) => {
+                    for entry in &entries {
+                        view.append_entry(entry);
+                    }
+                    view.scroll_to_end();
+                }
+                Err(e) => view
+                    .buffer
+                    .set_text(&format!("Could not read the journal: {}", e)),
+            }
+            if view.follow_toggle.is_active() {
+                view.stop_following();
+                view.start_following();
+            }
+        });
+    }
+
+    fn append_entry(&self, entry: &JournalEntry) {
+        let mut end = self.buffer.end_iter();
+        if entry.is_failure() {
+            let mark = self.buffer.create_mark(None, &end, true);
+            *self.last_failure.borrow_mut() = Some(mark);
+        }
+        let pid = entry.pid.map(|p| format!("[{}]", p)).unwrap_or_default();
+        let line = format!(
+            "{} {}{}: {}\n",
+            format_timestamp(entry.realtime),
+            entry.identifier,
+            pid,
+            entry.message
+        );
+        match entry.priority {
+            Some(priority) => {
+                self.buffer
+                    .insert_with_tags_by_name(&mut end, &line, &[priority.label()]);
+            }
+            None => self.buffer.insert(&mut end, &line),
+        }
+    }
+
+    fn scroll_to_end(&self) {
+        let mark = self
+            .buffer
+            .create_mark(None, &self.buffer.end_iter(), false);
+        self.text_view.scroll_mark_onscreen(&mark);
+        self.buffer.delete_mark(&mark);
+    }
+
+    fn jump_to_last_failure(&self) {
+        match self.last_failure.borrow().as_ref() {
+            Some(mark) => {
+                self.text_view.scroll_to_mark(mark, 0.0, true, 0.0, 0.3);
+                let start = self.buffer.iter_at_mark(mark);
+                let mut end = start;
+                end.forward_to_line_end();
+                self.buffer.select_range(&start, &end);
+            }
+            None => self.text_view.error_bell(),
+        }
+    }
+
+    fn start_following(self: &Rc<Self>) {
+        let Some(query) = self.query() else {
+            return;
+        };
+        let follower = match JournalFollower::start(&query) {
+            Ok(follower) => follower,
+            Err(e) => {
+                let mut end = self.buffer.end_iter();
+                self.buffer
+                    .insert(&mut end, &format!("Could not follow the journal: {}\n", e));
+                return;
+            }
+        };
+        let weak = Rc::downgrade(self);
+        let source = glib::timeout_add_local(Duration::from_millis(250), move || {
+            let Some(view) = weak.upgrade() else {
+                return glib::ControlFlow::Break;
+            };
+            let entries = match view.follower.borrow().as_ref() {
+                Some((follower, _)) => follower.drain(),
+                None => return glib::ControlFlow::Break,
+            };
+            if !entries.is_empty() {
+                for entry in &entries {
+                    view.append_entry(entry);
+                }
+                view.scroll_to_end();
+            }
+            glib::ControlFlow::Continue
+        });
+        *self.follower.borrow_mut() = Some((follower, source));
+    }
+
+    fn stop_following(&self) {
+        if let Some((_, source)) = self.follower.borrow_mut().take() {
+            source.remove();
+        }
+    }
+}
@@ -0,0 +1,257 @@
+use crate::backend::UnitScope;
+use crate::backend::new_service::{RESTART_POLICIES, ServiceSpec};
+use crate::backend::unitfile::{FileKind, Severity};
+use adw::prelude::*;
+use gtk4::{Box, Label, Orientation, PolicyType, ScrolledWindow, TextView, WrapMode};
+
+const SCOPES: [UnitScope; 2] = [UnitScope::System, UnitScope::User];
+
+/// Targets offered for `WantedBy=`; the empty one leaves `[Install]` out.
+const WANTED_BY: [&str; 4] = [
+    "multi-user.target",
+    "graphical.target",
+    "default.target",
+    "",
+];
+
+/// What the user asked for once the dialog is confirmed.
+pub struct NewService {
+    pub spec: ServiceSpec,
+    pub scope: UnitScope,
+    pub enable: bool,
+    pub start: bool,
+}
+
+#[derive(Clone)]
+struct Form {
+    name: adw::EntryRow,
+    description: adw::EntryRow,
+    exec_start: adw::EntryRow,
+    user: adw::EntryRow,
+    working_directory: adw::EntryRow,
+    restart: adw::ComboRow,
+    wanted_by: adw::ComboRow,
+    scope: adw::ComboRow,
+    environment: TextView,
+    enable: adw::SwitchRow,
+    start: adw::SwitchRow,
+}
+
+impl Form {
+    fn scope(&self) -> UnitScope {
+        SCOPES[self.scope.selected() as usize % SCOPES.len()]
+    }
+
+    fn spec(&self) -> ServiceSpec {
+        let buffer = self.environment.buffer();
+        let environment = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+        ServiceSpec {
+            name: self.name.text().to_string(),
+            description: self.description.text().to_string(),
+            exec_start: self.exec_start.text().to_string(),
+            user: match self.scope() {
+                UnitScope::System => self.user.text().to_string(),
+                UnitScope::User => String::new(),
+            },
+            working_directory: self.working_directory.text().to_string(),
+            restart: RESTART_POLICIES[self.restart.selected() as usize % RESTART_POLICIES.len()]
+                .to_string(),
+            environment: environment
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect(),
+            wanted_by: WANTED_BY[self.wanted_by.selected() as usize % WANTED_BY.len()].to_string(),
+        }
+    }
+
+    fn request(&self) -> NewService {
+        let spec = self.spec();
+        NewService {
+            // Without WantedBy= there is no [Install] section to enable.
+            enable: self.enable.is_active() && !spec.wanted_by.is_empty(),
+            spec,
+            scope: self.scope(),
+            start: self.start.is_active(),
+        }
+    }
+}
+
+/// Collects the settings of a new service and shows the unit file they make,
+/// with whatever the linter finds. Create stays disabled while there are
+/// errors.
+pub fn show_new_service_dialog(
+    parent: &impl IsA<gtk4::Widget>,
+    on_create: impl Fn(NewService) + 'static,
+) {
+    let entry_row = |title: &str| adw::EntryRow::builder().title(title).build();
+    let combo_row = |title: &str, items: &[&str]| {
+        adw::ComboRow::builder()
+            .title(title)
+            .model(&gtk4::StringList::new(items))
+            .build()
+    };
+    let wanted_by_labels = WANTED_BY.map(|target| if target.is_empty() { "None" } else { target });
+    let form = Form {
+        name: entry_row("Name"),
+        description: entry_row("Description"),
+        exec_start: entry_row("Command"),
+        user: entry_row("User"),
+        working_directory: entry_row("Working Directory"),
+        restart: combo_row("Restart", &RESTART_POLICIES),
+        wanted_by: combo_row("Wanted By", &wanted_by_labels),
+        scope: combo_row("Scope", &["System", "User"]),
+        environment: TextView::builder()
+            .monospace(true)
+            .wrap_mode(WrapMode::WordChar)
+            .height_request(60)
+            .top_margin(6)
+            .bottom_margin(6)
+            .left_margin(6)
+            .right_margin(6)
+            .build(),
+        enable: adw::SwitchRow::builder()
+            .title("Enable")
+            .subtitle("Start it whenever the wanted-by target starts")
+            .active(true)
+            .build(),
+        start: adw::SwitchRow::builder()
+            .title("Start Now")
+            .active(true)
+            .build(),
+    };
+    form.exec_start
+        .set_tooltip_text(Some("ExecStart=, an absolute path followed by arguments"));
+
+    let service_group = adw::PreferencesGroup::new();
+    for row in [
+        form.name.upcast_ref::<gtk4::Widget>(),
+        form.description.upcast_ref(),
+        form.exec_start.upcast_ref(),
+        form.user.upcast_ref(),
+        form.working_directory.upcast_ref(),
+        form.restart.upcast_ref(),
+    ] {
+        service_group.add(row);
+    }
+    let environment_group = adw::PreferencesGroup::builder()
+        .title("Environment")
+        .description("One KEY=value per line")
+        .build();
+    environment_group.add(
+        &gtk4::Frame::builder()
+            .child(&form.environment)
+            .css_classes(["view"])
+            .build(),
+    );
+    let install_group = adw::PreferencesGroup::new();
+    install_group.add(&form.scope);
+    install_group.add(&form.wanted_by);
+    install_group.add(&form.enable);
+    install_group.add(&form.start);
+
+    let preview = Label::builder()
+        .xalign(0.0)
+        .selectable(true)
+        .wrap(true)
+        .css_classes(["monospace", "caption"])
+        .build();
+    let problems = Label::builder()
+        .xalign(0.0)
+        .wrap(true)
+        .css_classes(["caption", "error"])
+        .build();
+
+    let content = Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(12)
+        .build();
+    content.append(&service_group);
+    content.append(&environment_group);
+    content.append(&install_group);
+    content.append(&problems);
+    content.append(&preview);
+    let scroll = ScrolledWindow::builder()
+        .hscrollbar_policy(PolicyType::Never)
+        .min_content_height(420)
+        .child(&content)
+        .build();
+
+    let dialog = adw::AlertDialog::builder()
+        .heading("New Service")
+        .body("Writes a unit file to /etc/systemd/system, or ~/.config/systemd/user for the user manager, and reloads systemd.")
+        .extra_child(&scroll)
+        .default_response("create")
+        .close_response("cancel")
+        .build();
+    dialog.add_response("cancel", "Cancel");
+    dialog.add_response("create", "Create");
+    dialog.set_response_appearance("create", adw::ResponseAppearance::Suggested);
+
+    let update = {
+        let (form, dialog) = (form.clone(), dialog.clone());
+        move || {
+            let spec = form.spec();
+            form.enable.set_sensitive(!spec.wanted_by.is_empty());
+            let file = spec.to_unit_file();
+            let diagnostics = file.lint(&spec.unit_name(), FileKind::Fragment);
+            let mut messages = spec.problems();
+            messages.extend(
+                diagnostics
+                    .iter()
+                    .filter(|d| d.severity == Severity::Error)
+                    .map(|d| d.message.clone()),
+            );
+            dialog.set_response_enabled("create", messages.is_empty());
+            messages.extend(
+                diagnostics
+                    .iter()
+                    .filter(|d| d.severity != Severity::Error)
+                    .map(|d| d.message.clone()),
+            );
+            problems.set_label(&messages.join("\n"));
+            problems.set_visible(!messages.is_empty());
+            preview.set_label(&format!("# {}\n{}", spec.unit_name(), file));
+        }
+    };
+    update();
+
+    for entry in [
+        &form.name,
+        &form.description,
+        &form.exec_start,
+        &form.user,
+        &form.working_directory,
+    ] {
+        let update = update.clone();
+        entry.connect_changed(move |_| update());
+    }
+    for combo in [&form.restart, &form.wanted_by] {
+        let update = update.clone();
+        combo.connect_selected_notify(move |_| update());
+    }
+    {
+        let update = update.clone();
+        form.environment.buffer().connect_changed(move |_| update());
+    }
+    {
+        let form_scope = form.clone();
+        form.scope.connect_selected_notify(move |_| {
+            let user = form_scope.scope() == UnitScope::User;
+            form_scope.user.set_visible(!user);
+            let default_target = if user {
+                "default.target"
+            } else {
+                "multi-user.target"
+            };
+            if let Some(index) = WANTED_BY.iter().position(|t| *t == default_target) {
+                form_scope.wanted_by.set_selected(index as u32);
+            }
+            update();
+        });
+    }
+
+    dialog.connect_response(Some("create"), move |_, _| on_create(form.request()));
+    dialog.present(Some(parent));
+}
@@ -0,0 +1,256 @@
+use super::detail::{format_bytes, format_cpu_time};
+use super::show_toast;
+use super::signal::show_process_signal_dialog;
+use crate::backend::processes::UnitProcess;
+use crate::backend::{Signal, SystemdServiceManager, UnitScope};
+use adw::prelude::*;
+use adw::{ToastOverlay, ToastPriority};
+use gtk4::{Align, Box, Button, Label, Orientation, Stack, glib};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::Duration;
+
+/// Indentation per tree level, in pixels.
+const INDENT: i32 = 18;
+
+/// Processes tab of the detail pane: the unit's control groups with their
+/// processes as a tree, each with a button to signal just that PID.
+pub struct ProcessView {
+    pub container: Box,
+    stack: Stack,
+    status: adw::StatusPage,
+    page: adw::PreferencesPage,
+    groups: RefCell<Vec<adw::PreferencesGroup>>,
+    summary: Label,
+    systemd: SystemdServiceManager,
+    toast_overlay: ToastOverlay,
+    unit: RefCell<Option<(String, UnitScope)>>,
+    stale: Cell<bool>,
+    generation: Cell<u64>,
+}
+
+impl ProcessView {
+    pub fn new(systemd: SystemdServiceManager, toast_overlay: ToastOverlay) -> Rc<Self> {
+        let summary = Label::builder()
+            .xalign(0.0)
+            .hexpand(true)
+            .css_classes(["dim-label"])
+            .build();
+        let refresh_button = Button::builder()
+            .icon_name("view-refresh-symbolic")
+            .tooltip_text("Re-read the processes")
+            .build();
+        let toolbar = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(6)
+            .margin_top(6)
+            .margin_bottom(6)
+            .margin_start(12)
+            .margin_end(12)
+            .build();
+        toolbar.append(&summary);
+        toolbar.append(&refresh_button);
+
+        let page = adw::PreferencesPage::new();
+        let status = adw::StatusPage::builder()
+            .icon_name("system-run-symbolic")
+            .build();
+        let stack = Stack::builder().vexpand(true).build();
+        stack.add_named(&page, Some("processes"));
+        stack.add_named(&status, Some("status"));
+
+        let container = Box::builder().orientation(Orientation::Vertical).build();
+        container.append(&toolbar);
+        container.append(&stack);
+
+        let view = Rc::new(Self {
+            container,
+            stack,
+            status,
+            page,
+            groups: RefCell::new(Vec::new()),
+            summary,
+            systemd,
+            toast_overlay,
+            unit: RefCell::new(None),
+            stale: Cell::new(false),
+            generation: Cell::new(0),
+        });
+
+        let view_map = Rc::clone(&view);
+        view.container.connect_map(move |_| {
+            if view_map.stale.get() {
+                view_map.reload();
+            }
+        });
+
+        let view_refresh = Rc::clone(&view);
+        refresh_button.connect_clicked(move |_| view_refresh.reload());
+
+        view
+    }
+
+    /// Switches to another unit; its processes are read once the tab is
+    /// shown.
+    pub fn set_unit(self: &Rc<Self>, unit: Option<(String, UnitScope)>) {
+        *self.unit.borrow_mut() = unit;
+        self.stale.set(true);
+        if self.container.is_mapped() {
+            self.reload();
+        }
+    }
+
+    fn reload(self: &Rc<Self>) {
+        self.stale.set(false);
+        let generation = self.generation.get() + 1;
+        self.generation.set(generation);
+        self.clear_groups();
+        self.summary.set_label("");
+        let Some((name, scope)) = self.unit.borrow().clone() else {
+            self.show_status("No Service Selected", None);
+            return;
+        };
+        self.show_status("Loading…", None);
+
+        let view = Rc::clone(self);
+        glib::spawn_future_local(async move {
+            let unit_name = name.clone();
+            let processes = view
+                .systemd
+                .run(move |systemd| systemd.unit_processes(&unit_name, scope))
+                .await;
+            if view.generation.get() != generation {
+                return;
+            }
+            match processes {
+                Ok(processes) if processes.is_empty() => {
+                    view.show_status(
+                        "No Processes",
+                        Some(&format!("{} has no running processes", name)),
+                    );
+                }
+                Ok(processes) => view.show_processes(processes),
+                Err(e) => view.show_status("Could Not Read Processes", Some(&e.to_string())),
+            }
+        });
+    }
+
+    fn clear_groups(&self) {
+        for group in self.groups.borrow_mut().drain(..) {
+            self.page.remove(&group);
+        }
+    }
+
+    fn show_processes(self: &Rc<Self>, processes: Vec<UnitProcess>) {
+        self.summary.set_label(&format!(
+            "{} process{}",
+            processes.len(),
+            if processes.len() == 1 { "" } else { "es" }
+        ));
+
+        // Tree order keeps children next to their parents, so a group is
+        // started whenever the control group changes.
+        let mut current: Option<(String, adw::PreferencesGroup)> = None;
+        for process in processes {
+            if current
+                .as_ref()
+                .is_none_or(|(cgroup, _)| *cgroup != process.cgroup)
+            {
+                let group = adw::PreferencesGroup::builder()
+                    .title(process.cgroup.as_str())
+                    .build();
+                self.page.add(&group);
+                self.groups.borrow_mut().push(group.clone());
+                current = Some((process.cgroup.clone(), group));
+            }
+            if let Some((_, group)) = &current {
+                group.add(&self.create_row(process));
+            }
+        }
+        self.stack.set_visible_child_name("processes");
+    }
+
+    fn create_row(self: &Rc<Self>, process: UnitProcess) -> adw::ActionRow {
+        let row = adw::ActionRow::builder()
+            .title(process.command.as_str())
+            .title_lines(2)
+            .subtitle(format_process(&process))
+            .use_markup(false)
+            .build();
+        if process.depth > 0 {
+            let branch = Label::builder()
+                .label("└")
+                .margin_start(INDENT * (process.depth as i32 - 1))
+                .css_classes(["dim-label"])
+                .build();
+            row.add_prefix(&branch);
+        }
+
+        let signal_button = Button::builder()
+            .icon_name("process-stop-symbolic")
+            .tooltip_text("Send a signal to this process")
+            .valign(Align::Center)
+            .css_classes(["flat"])
+            .build();
+        let view = Rc::clone(self);
+        signal_button.connect_clicked(move |button| {
+            let view = Rc::clone(&view);
+            let process_signal = process.clone();
+            show_process_signal_dialog(button, process.pid, &process.command, move |signal| {
+                view.send_signal(process_signal.clone(), signal)
+            });
+        });
+        row.add_suffix(&signal_button);
+        row
+    }
+
+    /// Signals one process, then reads the list again once it had a moment
+    /// to react.
+    fn send_signal(self: &Rc<Self>, process: UnitProcess, signal: Signal) {
+        let Some((name, scope)) = self.unit.borrow().clone() else {
+            return;
+        };
+        let view = Rc::clone(self);
+        glib::spawn_future_local(async move {
+            let pid = process.pid;
+            let result = view
+                .systemd
+                .run(move |systemd| systemd.signal_process(&name, scope, &process, signal))
+                .await;
+            match result {
+                Ok(()) => show_toast(
+                    &view.toast_overlay,
+                    &format!("Sent {} to process {}", signal.name(), pid),
+                    ToastPriority::Normal,
+                ),
+                Err(e) => show_toast(
+                    &view.toast_overlay,
+                    &format!("Failed to signal process {}: {}", pid, e),
+                    ToastPriority::High,
+                ),
+            }
+            glib::timeout_future(Duration::from_millis(500)).await;
+            view.reload();
+        });
+    }
+
+    fn show_status(&self, title: &str, description: Option<&str>) {
+        self.status.set_title(title);
+        self.status.set_description(description);
+        self.stack.set_visible_child_name("status");
+    }
+}
+
+fn format_process(process: &UnitProcess) -> String {
+    let mut parts = vec![format!("PID {}", process.pid)];
+    if let Some(user) = &process.user {
+        parts.push(user.clone());
+    }
+    if let Some(cpu_time) = process.cpu_time {
+        parts.push(format!("CPU {}", format_cpu_time(cpu_time)));
+    }
+    if let Some(rss) = process.rss {
+        parts.push(format!("RSS {}", format_bytes(rss)));
+    }
+    parts.join(" · ")
+}
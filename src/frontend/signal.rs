@@ -0,0 +1,116 @@
+use crate::backend::{KillWhom, Signal};
+use adw::prelude::*;
+use std::rc::Rc;
+
+/// Asks which signal to send to which processes of `units`. SIGKILL cannot
+/// be caught, so it needs a second confirmation before `on_send` runs.
+pub fn show_signal_dialog(
+    parent: &impl IsA<gtk4::Widget>,
+    units: &[String],
+    on_send: impl Fn(KillWhom, Signal) + 'static,
+) {
+    let signal_row = create_signal_row();
+    let whom_row = adw::ComboRow::builder()
+        .title("Processes")
+        .model(&gtk4::StringList::new(&[
+            "Main process",
+            "Control process",
+            "All processes",
+        ]))
+        .selected(2)
+        .build();
+    let group = adw::PreferencesGroup::new();
+    group.add(&signal_row);
+    group.add(&whom_row);
+
+    let target = units.join(", ");
+    let dialog = create_dialog(
+        &format!("Send a signal to the processes of {}.", target),
+        &group,
+    );
+    let parent = parent.clone().upcast::<gtk4::Widget>();
+    let on_send = Rc::new(on_send);
+    let confirm_parent = parent.clone();
+    dialog.connect_response(Some("send"), move |_, _| {
+        let signal = selected_signal(&signal_row);
+        let whom = KillWhom::ALL[whom_row.selected() as usize % KillWhom::ALL.len()];
+        let on_send = Rc::clone(&on_send);
+        confirm_signal(&confirm_parent, &target, signal, move || {
+            on_send(whom, signal)
+        });
+    });
+    dialog.present(Some(&parent));
+}
+
+/// Asks which signal to send to a single process, with the same SIGKILL
+/// confirmation as [`show_signal_dialog`].
+pub fn show_process_signal_dialog(
+    parent: &impl IsA<gtk4::Widget>,
+    pid: u32,
+    command: &str,
+    on_send: impl Fn(Signal) + 'static,
+) {
+    let signal_row = create_signal_row();
+    let group = adw::PreferencesGroup::new();
+    group.add(&signal_row);
+
+    let dialog = create_dialog(
+        &format!("Send a signal to process {}, {}.", pid, command),
+        &group,
+    );
+    let parent = parent.clone().upcast::<gtk4::Widget>();
+    let on_send = Rc::new(on_send);
+    let confirm_parent = parent.clone();
+    let target = format!("process {}", pid);
+    dialog.connect_response(Some("send"), move |_, _| {
+        let signal = selected_signal(&signal_row);
+        let on_send = Rc::clone(&on_send);
+        confirm_signal(&confirm_parent, &target, signal, move || on_send(signal));
+    });
+    dialog.present(Some(&parent));
+}
+
+fn create_signal_row() -> adw::ComboRow {
+    let signal_names = Signal::ALL.map(|signal| signal.name());
+    adw::ComboRow::builder()
+        .title("Signal")
+        .model(&gtk4::StringList::new(&signal_names))
+        .build()
+}
+
+fn selected_signal(row: &adw::ComboRow) -> Signal {
+    Signal::ALL[row.selected() as usize % Signal::ALL.len()]
+}
+
+fn create_dialog(body: &str, group: &adw::PreferencesGroup) -> adw::AlertDialog {
+    let dialog = adw::AlertDialog::builder()
+        .heading("Send Signal")
+        .body(body)
+        .extra_child(group)
+        .default_response("send")
+        .close_response("cancel")
+        .build();
+    dialog.add_response("cancel", "Cancel");
+    dialog.add_response("send", "Send");
+    dialog.set_response_appearance("send", adw::ResponseAppearance::Suggested);
+    dialog
+}
+
+/// Runs `send` right away, or after a destructive confirmation for SIGKILL.
+fn confirm_signal(parent: &gtk4::Widget, target: &str, signal: Signal, send: impl Fn() + 'static) {
+    if signal != Signal::Kill {
+        send();
+        return;
+    }
+    let confirm = adw::AlertDialog::builder()
+        .heading(format!("Kill {}?", target))
+        .body("SIGKILL ends the processes immediately. They get no chance to clean up, so unsaved data may be lost.")
+        .default_response("cancel")
+        .close_response("cancel")
+        .build();
+    confirm.add_response("cancel", "Cancel");
+    confirm.add_response("kill", "Kill");
+    confirm.set_response_appearance("kill", adw::ResponseAppearance::Destructive);
+    confirm.connect_response(Some("kill"), move |_, _| send());
+    confirm.present(Some(parent));
+}
@@ -0,0 +1,201 @@
+use super::detail::format_timestamp;
+use super::{format_scope, next_elapse, show_toast};
+use crate::backend::SystemdServiceManager;
+use crate::backend::timers::TimerInfo;
+use adw::prelude::*;
+use adw::{ToastOverlay, ToastPriority};
+use gtk4::{Align, Button, Justification, Label, glib};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// Page listing every timer ordered by its next run, with a countdown that
+/// ticks once a second while the page is visible.
+pub struct TimersView {
+    pub container: adw::PreferencesPage,
+    group: adw::PreferencesGroup,
+    /// The shown rows with their next elapse (wall clock, µs) and the label
+    /// holding the countdown.
+    rows: RefCell<Vec<(adw::ActionRow, Option<u64>, Label)>>,
+    systemd: SystemdServiceManager,
+    toast_overlay: ToastOverlay,
+}
+
+impl TimersView {
+    pub fn new(systemd: SystemdServiceManager, toast_overlay: ToastOverlay) -> Rc<Self> {
+        let refresh_button = Button::builder()
+            .icon_name("view-refresh-symbolic")
+            .tooltip_text("Refresh timers")
+            .valign(Align::Center)
+            .css_classes(["flat"])
+            .build();
+        let group = adw::PreferencesGroup::builder()
+            .title("Timers")
+            .description("Scheduled units of both managers, soonest first")
+            .header_suffix(&refresh_button)
+            .build();
+        let container = adw::PreferencesPage::new();
+        container.add(&group);
+
+        let view = Rc::new(Self {
+            container,
+            group,
+            rows: RefCell::new(Vec::new()),
+            systemd,
+            toast_overlay,
+        });
+
+        let view_refresh = Rc::clone(&view);
+        refresh_button.connect_clicked(move |_| view_refresh.refresh());
+
+        let view_tick = Rc::clone(&view);
+        glib::timeout_add_local(Duration::from_secs(1), move || {
+            if view_tick.container.is_mapped() {
+                view_tick.update_countdowns();
+            }
+            glib::ControlFlow::Continue
+        });
+
+        view.show_timers(Vec::new());
+        view
+    }
+
+    pub fn refresh(self: &Rc<Self>) {
+        let view = Rc::clone(self);
+        glib::spawn_future_local(async move {
+            let timers = view.systemd.run(|systemd| systemd.list_timers()).await;
+            match timers {
+                Ok(timers) => view.show_timers(timers),
+                Err(e) => show_toast(
+                    &view.toast_overlay,
+                    &format!("Failed to list timers: {}", e),
+                    ToastPriority::High,
+                ),
+            }
+        });
+    }
+
+    fn show_timers(self: &Rc<Self>, timers: Vec<TimerInfo>) {
+        for (row, ..) in self.rows.borrow_mut().drain(..) {
+            self.group.remove(&row);
+        }
+
+        if timers.is_empty() {
+            let row = adw::ActionRow::builder()
+                .title("No timers loaded")
+                .css_classes(["dim-label"])
+                .build();
+            self.group.add(&row);
+            self.rows.borrow_mut().push((row, None, Label::new(None)));
+            return;
+        }
+
+        let mut timers: Vec<(Option<u64>, TimerInfo)> = timers
+            .into_iter()
+            .map(|timer| {
+                let next = next_elapse(timer.next_elapse_realtime, timer.next_elapse_monotonic);
+                (next, timer)
+            })
+            .collect();
+        // Timers without a next run go last.
+        timers.sort_by_key(|(next, timer)| (next.is_none(), *next, timer.name.clone()));
+
+        for (next, timer) in timers {
+            let row = adw::ActionRow::builder()
+                .title(timer.name.as_str())
+                .subtitle(format!(
+                    "Activates {} · Last: {}{} · {} · {}",
+                    timer.unit,
+                    format_timestamp(timer.last_trigger),
+                    match timer.result.as_str() {
+                        "" | "success" => String::new(),
+                        result => format!(" ({})", result),
+                    },
+                    timer.active_state,
+                    format_scope(timer.scope)
+                ))
+                .use_markup(false)
+                .build();
+
+            let countdown = Label::builder()
+                .justify(Justification::Right)
+                .css_classes(["numeric"])
+                .build();
+            row.add_suffix(&countdown);
+
+            let run_button = Button::builder()
+                .label("Run Now")
+                .tooltip_text(format!("Start {}", timer.unit))
+                .valign(Align::Center)
+                .build();
+            let view = Rc::clone(self);
+            run_button.connect_clicked(move |_| view.run_now(&timer));
+            row.add_suffix(&run_button);
+
+            self.group.add(&row);
+            self.rows.borrow_mut().push((row, next, countdown));
+        }
+        self.update_countdowns();
+    }
+
+    fn update_countdowns(&self) {
+        let now = glib::real_time().max(0) as u64;
+        for (_, next, label) in self.rows.borrow().iter() {
+            let text = match next {
+                Some(next) => format!(
+                    "{}\n{}",
+                    format_timestamp(*next),
+                    format_countdown(next.saturating_sub(now))
+                ),
+                None => "No next run".to_string(),
+            };
+            label.set_label(&text);
+        }
+    }
+
+    /// Starts the unit the timer activates, as systemd would on elapse. The
+    /// timer's own last trigger time is left alone.
+    fn run_now(self: &Rc<Self>, timer: &TimerInfo) {
+        let view = Rc::clone(self);
+        let (unit, scope) = (timer.unit.clone(), timer.scope);
+        glib::spawn_future_local(async move {
+            let name = unit.clone();
+            let result = view
+                .systemd
+                .run(move |systemd| systemd.start_unit(&name, scope))
+                .await;
+            match result {
+                Ok(result) if result.is_success() => show_toast(
+                    &view.toast_overlay,
+                    &format!("Started {}", unit),
+                    ToastPriority::Normal,
+                ),
+                Ok(result) => show_toast(
+                    &view.toast_overlay,
+                    &format!("Failed to start {}: {}", unit, result.describe()),
+                    ToastPriority::High,
+                ),
+                Err(e) => show_toast(
+                    &view.toast_overlay,
+                    &format!("Failed to start {}: {}", unit, e),
+                    ToastPriority::High,
+                ),
+            }
+        });
+    }
+}
+
+/// Formats the time left until an elapse, dropping the seconds once it is
+/// more than a day away.
+fn format_countdown(usec: u64) -> String {
+    let secs = usec / 1_000_000;
+    let (days, hours, minutes, seconds) =
+        (secs / 86_400, secs / 3_600 % 24, secs / 60 % 60, secs % 60);
+    match (days, hours, minutes) {
+        (0, 0, 0) if seconds == 0 => "due now".to_string(),
+        (0, 0, 0) => format!("in {}s", seconds),
+        (0, 0, _) => format!("in {}m {:02}s", minutes, seconds),
+        (0, _, _) => format!("in {}h {:02}m {:02}s", hours, minutes, seconds),
+        _ => format!("in {}d {:02}h {:02}m", days, hours, minutes),
+    }
+}
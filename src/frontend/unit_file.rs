@@ -0,0 +1,463 @@
+use super::show_toast;
+use crate::backend::overrides::UnitSource;
+use crate::backend::unitfile::{self, Diagnostic, FileKind, Severity, UnitFile};
+use crate::backend::{SystemdServiceManager, UnitScope, UnitType};
+use adw::prelude::*;
+use adw::{ToastOverlay, ToastPriority};
+use gtk4::{
+    Box, Button, Image, Label, ListBox, Orientation, PolicyType, ScrolledWindow, SelectionMode,
+    TextBuffer, TextTag, TextView, WrapMode, gdk, glib, pango,
+};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+/// Unit file tab of the detail pane. Shows the fragment and drop-ins like
+/// `systemctl cat` and switches to an editor for the unit's
+/// `override.conf`. Saving reloads the manager so the override applies.
+/// Whatever is shown is linted, with problems underlined and listed below.
+pub struct UnitFileView {
+    pub container: Box,
+    buffer: TextBuffer,
+    text_view: TextView,
+    diagnostics_list: ListBox,
+    diagnostics_scroll: ScrolledWindow,
+    diagnostic_lines: RefCell<Vec<Option<usize>>>,
+    lint_kind: Cell<Option<FileKind>>,
+    /// The buffer has lint errors; saving is refused until they are fixed.
+    lint_errors: Cell<bool>,
+    saving: Cell<bool>,
+    path_label: Label,
+    edit_button: Button,
+    save_button: Button,
+    cancel_button: Button,
+    systemd: SystemdServiceManager,
+    toast_overlay: ToastOverlay,
+    unit: RefCell<Option<(String, UnitScope)>>,
+    source: RefCell<Option<UnitSource>>,
+    editing: Cell<bool>,
+    stale: Cell<bool>,
+    generation: Cell<u64>,
+}
+
+impl UnitFileView {
+    pub fn new(systemd: SystemdServiceManager, toast_overlay: ToastOverlay) -> Rc<Self> {
+        let buffer = TextBuffer::new(None);
+        for (name, color) in [
+            ("comment", "#77767b"),
+            ("section", "#1c71d8"),
+            ("key", "#c64600"),
+        ] {
+            let tag = TextTag::builder().name(name).foreground(color).build();
+            if name == "section" {
+                tag.set_weight(700);
+            }
+            buffer.tag_table().add(&tag);
+        }
+        for (name, color) in [
+            ("error", gdk::RGBA::new(0.88, 0.11, 0.14, 1.0)),
+            ("warning", gdk::RGBA::new(0.9, 0.65, 0.04, 1.0)),
+        ] {
+            let tag = TextTag::builder()
+                .name(name)
+                .underline(pango::Underline::Error)
+                .underline_rgba(&color)
+                .build();
+            buffer.tag_table().add(&tag);
+        }
+
+        let text_view = TextView::builder()
+            .buffer(&buffer)
+            .editable(false)
+            .monospace(true)
+            .wrap_mode(WrapMode::WordChar)
+            .left_margin(12)
+            .right_margin(12)
+            .top_margin(6)
+            .bottom_margin(6)
+            .build();
+
+        let path_label = Label::builder()
+            .hexpand(true)
+            .xalign(0.0)
+            .ellipsize(pango::EllipsizeMode::Middle)
+            .css_classes(["caption", "dim-label"])
+            .build();
+        let edit_button = Button::builder()
+            .label("Edit Override")
+            .tooltip_text("Create or edit the unit's override.conf drop-in")
+            .build();
+        let cancel_button = Button::builder().label("Cancel").visible(false).build();
+        let save_button = Button::builder()
+            .label("Save")
+            .tooltip_text("Write override.conf and reload the manager")
+            .css_classes(["suggested-action"])
+            .visible(false)
+            .build();
+        let refresh_button = Button::builder()
+            .icon_name("view-refresh-symbolic")
+            .tooltip_text("Re-read the unit files")
+            .build();
+
+        let toolbar = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(6)
+            .margin_top(6)
+            .margin_bottom(6)
+            .margin_start(12)
+            .margin_end(12)
+            .build();
+        toolbar.append(&path_label);
+        toolbar.append(&edit_button);
+        toolbar.append(&cancel_button);
+        toolbar.append(&save_button);
+        toolbar.append(&refresh_button);
+
+        let scroll = ScrolledWindow::builder()
+            .hscrollbar_policy(PolicyType::Never)
+            .child(&text_view)
+            .vexpand(true)
+            .build();
+
+        let diagnostics_list = ListBox::builder()
+            .selection_mode(SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .margin_top(6)
+            .margin_bottom(6)
+            .margin_start(12)
+            .margin_end(12)
+            .build();
+        let diagnostics_scroll = ScrolledWindow::builder()
+            .hscrollbar_policy(PolicyType::Never)
+            .max_content_height(160)
+            .propagate_natural_height(true)
+            .child(&diagnostics_list)
+            .visible(false)
+            .build();
+
+        let container = Box::builder().orientation(Orientation::Vertical).build();
+        container.append(&toolbar);
+        container.append(&scroll);
+        container.append(&diagnostics_scroll);
+
+        let view = Rc::new(Self {
+            container,
+            buffer,
+            text_view,
+            diagnostics_list,
+            diagnostics_scroll,
+            diagnostic_lines: RefCell::new(Vec::new()),
+            lint_kind: Cell::new(None),
+            lint_errors: Cell::new(false),
+            saving: Cell::new(false),
+            path_label,
+            edit_button,
+            save_button,
+            cancel_button,
+            systemd,
+            toast_overlay,
+            unit: RefCell::new(None),
+            source: RefCell::new(None),
+            editing: Cell::new(false),
+            stale: Cell::new(false),
+            generation: Cell::new(0),
+        });
+
+        let view_map = Rc::clone(&view);
+        view.container.connect_map(move |_| {
+            if view_map.stale.get() {
+                view_map.reload();
+            }
+        });
+
+        let view_changed = Rc::clone(&view);
+        view.buffer.connect_changed(move |buffer| {
+            highlight(buffer);
+            view_changed.update_diagnostics();
+        });
+
+        let view_row = Rc::clone(&view);
+        view.diagnostics_list.connect_row_activated(move |_, row| {
+            let line = usize::try_from(row.index())
+                .ok()
+                .and_then(|index| view_row.diagnostic_lines.borrow().get(index).copied())
+                .flatten();
+            if let Some(line) = line {
+                view_row.go_to_line(line);
+            }
+        });
+
+        let view_edit = Rc::clone(&view);
+        view.edit_button
+            .connect_clicked(move |_| view_edit.start_editing());
+
+        let view_cancel = Rc::clone(&view);
+        view.cancel_button
+            .connect_clicked(move |_| view_cancel.stop_editing());
+
+        let view_save = Rc::clone(&view);
+        view.save_button.connect_clicked(move |_| view_save.save());
+
+        let view_refresh = Rc::clone(&view);
+        refresh_button.connect_clicked(move |_| view_refresh.reload());
+
+        view
+    }
+
+    /// Switches to another unit, dropping unsaved edits; its files are read
+    /// once the tab is shown.
+    pub fn set_unit(self: &Rc<Self>, unit: Option<(String, UnitScope)>) {
+        *self.unit.borrow_mut() = unit;
+        self.stale.set(true);
+        if self.container.is_mapped() {
+            self.reload();
+        }
+    }
+
+    fn reload(self: &Rc<Self>) {
+        self.stale.set(false);
+        self.set_editing(false);
+        *self.source.borrow_mut() = None;
+        let generation = self.generation.get() + 1;
+        self.generation.set(generation);
+        let Some((name, scope)) = self.unit.borrow().clone() else {
+            self.show_text("", None);
+            return;
+        };
+        self.show_text("Loading…", None);
+        self.path_label.set_label("");
+
+        let view = Rc::clone(self);
+        glib::spawn_future_local(async move {
+            let source = view
+                .systemd
+                .run(move |systemd| systemd.read_unit_source(&name, scope))
+                .await;
+            if view.generation.get() != generation {
+                return;
+            }
+            match source {
+                Ok(source) if source.files.is_empty() => {
+                    view.show_text("# This unit has no unit file.", None);
+                    *view.source.borrow_mut() = Some(source);
+                }
+                Ok(source) => {
+                    view.show_text(&source.merged(), Some(FileKind::Fragment));
+                    *view.source.borrow_mut() = Some(source);
+                }
+                Err(e) => view.show_text(&format!("# Could not read the unit files: {}", e), None),
+            }
+        });
+    }
+
+    fn start_editing(&self) {
+        let Some((name, _)) = self.unit.borrow().clone() else {
+            return;
+        };
+        let source = self.source.borrow();
+        let Some(source) = source.as_ref() else {
+            return;
+        };
+        let content = source
+            .override_content()
+            .map(str::to_string)
+            .unwrap_or_else(|| {
+                let section = UnitType::from_name(&name)
+                    .and_then(unitfile::type_section)
+                    .unwrap_or("Unit");
+                format!("[{}]\n", section)
+            });
+        self.path_label
+            .set_label(&source.override_path.display().to_string());
+        self.show_text(&content, Some(FileKind::DropIn));
+        self.set_editing(true);
+        self.text_view.grab_focus();
+    }
+
+    fn stop_editing(&self) {
+        self.set_editing(false);
+        self.path_label.set_label("");
+        let source = self.source.borrow();
+        match source.as_ref().filter(|source| !source.files.is_empty()) {
+            Some(source) => self.show_text(&source.merged(), Some(FileKind::Fragment)),
+            None => self.show_text("# This unit has no unit file.", None),
+        }
+    }
+
+    /// Replaces the buffer content; `lint` says how to check it, `None` for
+    /// placeholder text.
+    fn show_text(&self, text: &str, lint: Option<FileKind>) {
+        self.lint_kind.set(lint);
+        self.buffer.set_text(text);
+    }
+
+    fn update_diagnostics(&self) {
+        let (start, end) = self.buffer.bounds();
+        self.buffer.remove_tag_by_name("error", &start, &end);
+        self.buffer.remove_tag_by_name("warning", &start, &end);
+        while let Some(row) = self.diagnostics_list.first_child() {
+            self.diagnostics_list.remove(&row);
+        }
+        let unit = self.unit.borrow();
+        let diagnostics = match (self.lint_kind.get(), unit.as_ref()) {
+            (Some(kind), Some((name, _))) => {
+                UnitFile::parse(&self.buffer.text(&start, &end, false)).lint(name, kind)
+            }
+            _ => Vec::new(),
+        };
+
+        for diagnostic in &diagnostics {
+            if let Some(line) = diagnostic.line {
+                self.tag_line(line, diagnostic.severity);
+            }
+            self.diagnostics_list
+                .append(&create_diagnostic_row(diagnostic));
+        }
+        *self.diagnostic_lines.borrow_mut() = diagnostics.iter().map(|d| d.line).collect();
+        self.diagnostics_scroll.set_visible(!diagnostics.is_empty());
+        self.lint_errors
+            .set(diagnostics.iter().any(|d| d.severity == Severity::Error));
+        self.update_save_sensitivity();
+    }
+
+    fn update_save_sensitivity(&self) {
+        let errors = self.lint_errors.get();
+        self.save_button
+            .set_sensitive(!errors && !self.saving.get());
+        self.save_button.set_tooltip_text(Some(if errors {
+            "Fix the errors below before saving"
+        } else {
+            "Write override.conf and reload the manager"
+        }));
+    }
+
+    fn tag_line(&self, line: usize, severity: Severity) {
+        let tag = match severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => return,
+        };
+        let Some(start) = self.buffer.iter_at_line(line as i32 - 1) else {
+            return;
+        };
+        let mut end = start;
+        if !end.ends_line() {
+            end.forward_to_line_end();
+        }
+        self.buffer.apply_tag_by_name(tag, &start, &end);
+    }
+
+    fn go_to_line(&self, line: usize) {
+        if let Some(mut iter) = self.buffer.iter_at_line(line as i32 - 1) {
+            self.buffer.place_cursor(&iter);
+            self.text_view
+                .scroll_to_iter(&mut iter, 0.1, false, 0.0, 0.0);
+            self.text_view.grab_focus();
+        }
+    }
+
+    fn set_editing(&self, editing: bool) {
+        self.editing.set(editing);
+        self.text_view.set_editable(editing);
+        self.text_view.set_cursor_visible(editing);
+        self.edit_button.set_visible(!editing);
+        self.save_button.set_visible(editing);
+        self.cancel_button.set_visible(editing);
+    }
+
+    fn save(self: &Rc<Self>) {
+        if !self.editing.get() || self.lint_errors.get() || self.saving.get() {
+            return;
+        }
+        let Some((name, scope)) = self.unit.borrow().clone() else {
+            return;
+        };
+        let content = self
+            .buffer
+            .text(&self.buffer.start_iter(), &self.buffer.end_iter(), false)
+            .to_string();
+        self.saving.set(true);
+        self.update_save_sensitivity();
+
+        let view = Rc::clone(self);
+        glib::spawn_future_local(async move {
+            let unit = name.clone();
+            let result = view
+                .systemd
+                .run(move |systemd| {
+                    let path = systemd.write_override(&unit, scope, &content)?;
+                    systemd.daemon_reload(scope)?;
+                    Ok::<_, crate::backend::ServiceError>(path)
+                })
+                .await;
+            view.saving.set(false);
+            view.update_save_sensitivity();
+            match result {
+                Ok(path) => {
+                    show_toast(
+                        &view.toast_overlay,
+                        &format!("Saved {} and reloaded systemd", path.display()),
+                        ToastPriority::Normal,
+                    );
+                    view.reload();
+                }
+                Err(e) => show_toast(
+                    &view.toast_overlay,
+                    &format!("Failed to save the override for {}: {}", name, e),
+                    ToastPriority::High,
+                ),
+            }
+        });
+    }
+}
+
+fn create_diagnostic_row(diagnostic: &Diagnostic) -> adw::ActionRow {
+    let icon = match diagnostic.severity {
+        Severity::Error => "dialog-error-symbolic",
+        Severity::Warning => "dialog-warning-symbolic",
+        Severity::Info => "dialog-information-symbolic",
+    };
+    let row = adw::ActionRow::builder()
+        .title(&diagnostic.message)
+        .subtitle(
+            diagnostic
+                .line
+                .map(|line| format!("Line {}", line))
+                .unwrap_or_else(|| "Whole file".to_string()),
+        )
+        .use_markup(false)
+        .activatable(diagnostic.line.is_some())
+        .build();
+    row.add_prefix(&Image::from_icon_name(icon));
+    row
+}
+
+/// Tags comments, section headers and keys, line by line.
+fn highlight(buffer: &TextBuffer) {
+    buffer.remove_all_tags(&buffer.start_iter(), &buffer.end_iter());
+    for line in 0..buffer.line_count() {
+        let Some(start) = buffer.iter_at_line(line) else {
+            continue;
+        };
+        let mut end = start;
+        if !end.ends_line() {
+            end.forward_to_line_end();
+        }
+        let text = buffer.text(&start, &end, false);
+        let trimmed = text.trim_start();
+        let indent = (text.chars().count() - trimmed.chars().count()) as i32;
+        let (tag, length) = if trimmed.starts_with('#') || trimmed.starts_with(';') {
+            ("comment", trimmed.chars().count())
+        } else if trimmed.starts_with('[') {
+            ("section", trimmed.trim_end().chars().count())
+        } else if let Some((key, _)) = trimmed.split_once('=') {
+            ("key", key.trim_end().chars().count())
+        } else {
+            continue;
+        };
+        let mut tag_start = start;
+        tag_start.forward_chars(indent);
+        let mut tag_end = tag_start;
+        tag_end.forward_chars(length as i32);
+        buffer.apply_tag_by_name(tag, &tag_start, &tag_end);
+    }
+}